@@ -0,0 +1,345 @@
+use crate::op_prelude::*;
+use crate::time::Timer;
+use std::mem;
+use std::time::Duration;
+
+pin_project! {
+    /// Stream for the [`chunks_timeout`](super::JStreamExt::chunks_timeout) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct ChunksTimeout<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        max_items: usize,
+        max_delay: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        items: Vec<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for ChunksTimeout<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if *this.src_done {
+                return Poll::Ready(None);
+            }
+
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    if this.items.is_empty() {
+                        this.delay.set(Some(this.timer.delay(*this.max_delay)));
+                    }
+
+                    this.items.push(next);
+                    if this.items.len() >= *this.max_items {
+                        this.delay.set(None);
+                        return Poll::Ready(Some(take_batch(this.items, *this.max_items)));
+                    }
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                    return Poll::Ready(if this.items.is_empty() {
+                        None
+                    } else {
+                        Some(take_batch(this.items, 0))
+                    });
+                }
+                Poll::Pending => {
+                    if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+                        ready!(delay.poll(cx));
+                        this.delay.set(None);
+                        return Poll::Ready(Some(take_batch(this.items, *this.max_items)));
+                    }
+
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.src_done {
+            (0, Some(0))
+        } else {
+            chunks_size_hint(self.src.size_hint(), self.items.len(), self.max_items, 0)
+        }
+    }
+}
+
+impl<S, T> FusedStream for ChunksTimeout<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for ChunksTimeout<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> ChunksTimeout<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, max_items: usize, max_delay: Duration, timer: T) -> Self {
+        let max_items = max_items.max(1);
+        Self {
+            src,
+            timer,
+            max_items,
+            max_delay,
+            delay: None,
+            items: Vec::with_capacity(max_items),
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> ChunksTimeout<S, T2> {
+        ChunksTimeout::new(self.src, self.max_items, self.max_delay, timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_chunks_timeout`](super::JTryStreamExt::try_chunks_timeout) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryChunksTimeout<S, T>
+    where
+        S: TryStream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        max_items: usize,
+        max_delay: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        items: Vec<S::Ok>,
+        error: Option<S::Error>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for TryChunksTimeout<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    type Item = Result<Vec<S::Ok>, S::Error>;
+
+    //noinspection DuplicatedCode
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        loop {
+            if *this.src_done {
+                return Poll::Ready(None);
+            }
+
+            match this.src.as_mut().try_poll_next(cx) {
+                Poll::Ready(Some(Ok(next))) => {
+                    if this.items.is_empty() {
+                        this.delay.set(Some(this.timer.delay(*this.max_delay)));
+                    }
+
+                    this.items.push(next);
+                    if this.items.len() >= *this.max_items {
+                        this.delay.set(None);
+                        return Poll::Ready(Some(Ok(take_batch(this.items, *this.max_items))));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    if this.items.is_empty() {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+
+                    // flush the partial batch first, the error is emitted on the next poll
+                    *this.error = Some(err);
+                    this.delay.set(None);
+                    return Poll::Ready(Some(Ok(take_batch(this.items, *this.max_items))));
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                    return Poll::Ready(if this.items.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(take_batch(this.items, 0)))
+                    });
+                }
+                Poll::Pending => {
+                    if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+                        ready!(delay.poll(cx));
+                        this.delay.set(None);
+                        return Poll::Ready(Some(Ok(take_batch(this.items, *this.max_items))));
+                    }
+
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let error = if self.error.is_some() { 1 } else { 0 };
+        if self.src_done {
+            (error, Some(error))
+        } else {
+            let (lower, upper) =
+                chunks_size_hint(self.src.size_hint(), self.items.len(), self.max_items, error);
+            (lower.saturating_add(error), upper)
+        }
+    }
+}
+
+impl<S, T> FusedStream for TryChunksTimeout<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.error.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item, E> Sink<Item> for TryChunksTimeout<S, T>
+where
+    S: TryStream + Sink<Item, Error=E>,
+    T: Timer,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, T> TryChunksTimeout<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, max_items: usize, max_delay: Duration, timer: T) -> Self {
+        let max_items = max_items.max(1);
+        Self {
+            src,
+            timer,
+            max_items,
+            max_delay,
+            delay: None,
+            items: Vec::with_capacity(max_items),
+            error: None,
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> TryChunksTimeout<S, T2> {
+        TryChunksTimeout::new(self.src, self.max_items, self.max_delay, timer)
+    }
+}
+
+fn take_batch<T>(items: &mut Vec<T>, capacity: usize) -> Vec<T> {
+    mem::replace(items, Vec::with_capacity(capacity))
+}
+
+fn chunks_size_hint(
+    (src_lower, src_upper): (usize, Option<usize>),
+    buffered: usize,
+    max_items: usize,
+    extra: usize,
+) -> (usize, Option<usize>) {
+    // batches can be cut short by the timer, so every item could end up in a batch of its own
+    let lower = src_lower.saturating_add(buffered);
+    let lower = lower / max_items + if lower % max_items == 0 { 0 } else { 1 };
+    let upper = src_upper
+        .and_then(|upper| upper.checked_add(if buffered > 0 { 1 } else { 0 }))
+        .and_then(|upper| upper.checked_add(extra));
+    (lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunksTimeout, TryChunksTimeout};
+    use crate::test_util::TimedHarness;
+    use crate::time::VirtualClock;
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_chunks_timeout_full() {
+        let clock = VirtualClock::new();
+        let chunks = ChunksTimeout::new(futures::stream::iter(1..=7), 3, Duration::from_secs(1), clock);
+        assert_eq!(
+            block_on(chunks.collect::<Vec<_>>()),
+            vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]
+        );
+    }
+
+    #[test]
+    fn test_chunks_timeout_partial() {
+        let mut harness = TimedHarness::new();
+        let mut chunks = ChunksTimeout::new(
+            harness.source(),
+            3,
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        assert_eq!(harness.poll(&mut chunks), Poll::Pending);
+        assert_eq!(harness.clock().pending_delays(), 0);
+
+        // the timer starts with the first item of each batch
+        harness.advance(Duration::from_secs(5));
+        harness.send(1);
+        assert_eq!(harness.poll(&mut chunks), Poll::Pending);
+        harness.advance(Duration::from_millis(500));
+        harness.send(2);
+        assert_eq!(harness.poll(&mut chunks), Poll::Pending);
+        harness.advance(Duration::from_millis(500));
+        assert_eq!(harness.poll(&mut chunks), Poll::Ready(Some(vec![1, 2])));
+        assert_eq!(harness.poll(&mut chunks), Poll::Pending);
+
+        harness.send(3);
+        harness.close();
+        assert_eq!(harness.poll(&mut chunks), Poll::Ready(Some(vec![3])));
+        assert_eq!(harness.poll(&mut chunks), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_try_chunks_timeout_flushes_before_error() {
+        let clock = VirtualClock::new();
+        let src = futures::stream::iter(vec![Ok(1), Ok(2), Err("oh no!"), Err("again"), Ok(3)]);
+        let chunks = TryChunksTimeout::new(src, 5, Duration::from_secs(1), clock);
+        assert_eq!(
+            block_on(chunks.collect::<Vec<_>>()),
+            vec![Ok(vec![1, 2]), Err("oh no!"), Err("again"), Ok(vec![3])]
+        );
+    }
+}
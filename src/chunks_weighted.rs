@@ -0,0 +1,287 @@
+use crate::op_prelude::*;
+use std::mem;
+
+pin_project! {
+    /// Stream for the [`chunks_weighted`](super::JStreamExt::chunks_weighted) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct ChunksWeighted<S, F>
+    where
+        S: Stream,
+    {
+        #[pin]
+        src: S,
+        weight_fn: F,
+        max_weight: usize,
+        items: Vec<S::Item>,
+        weight: usize,
+        src_done: bool,
+    }
+}
+
+impl<S, F> Stream for ChunksWeighted<S, F>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> usize,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if *this.src_done {
+                break None;
+            }
+
+            if *this.weight >= *this.max_weight && !this.items.is_empty() {
+                *this.weight = 0;
+                break Some(mem::take(this.items));
+            }
+
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    let weight = (this.weight_fn)(&next);
+                    if !this.items.is_empty() && this.weight.saturating_add(weight) > *this.max_weight {
+                        // this item starts the next batch
+                        let batch = mem::replace(this.items, vec![next]);
+                        *this.weight = weight;
+                        break Some(batch);
+                    }
+
+                    this.items.push(next);
+                    *this.weight = this.weight.saturating_add(weight);
+                }
+                None => {
+                    *this.src_done = true;
+                    break if this.items.is_empty() {
+                        None
+                    } else {
+                        Some(mem::take(this.items))
+                    };
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.src_done {
+            (0, Some(0))
+        } else {
+            weighted_size_hint(self.src.size_hint(), !self.items.is_empty())
+        }
+    }
+}
+
+impl<S, F> FusedStream for ChunksWeighted<S, F>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> usize,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, F, Item> Sink<Item> for ChunksWeighted<S, F>
+where
+    S: Stream + Sink<Item>,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, F> ChunksWeighted<S, F>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> usize,
+{
+    pub(crate) fn new(src: S, max_weight: usize, weight_fn: F) -> Self {
+        Self {
+            src,
+            weight_fn,
+            max_weight,
+            items: Vec::new(),
+            weight: 0,
+            src_done: false,
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_chunks_weighted`](super::JTryStreamExt::try_chunks_weighted) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryChunksWeighted<S, F>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        src: S,
+        weight_fn: F,
+        max_weight: usize,
+        items: Vec<S::Ok>,
+        weight: usize,
+        error: Option<S::Error>,
+        src_done: bool,
+    }
+}
+
+impl<S, F> Stream for TryChunksWeighted<S, F>
+where
+    S: TryStream,
+    F: FnMut(&S::Ok) -> usize,
+{
+    type Item = Result<Vec<S::Ok>, S::Error>;
+
+    //noinspection DuplicatedCode
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if let Some(err) = this.error.take() {
+                break Some(Err(err));
+            }
+
+            if *this.src_done {
+                break None;
+            }
+
+            if *this.weight >= *this.max_weight && !this.items.is_empty() {
+                *this.weight = 0;
+                break Some(Ok(mem::take(this.items)));
+            }
+
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => {
+                    let weight = (this.weight_fn)(&next);
+                    if !this.items.is_empty() && this.weight.saturating_add(weight) > *this.max_weight {
+                        // this item starts the next batch
+                        let batch = mem::replace(this.items, vec![next]);
+                        *this.weight = weight;
+                        break Some(Ok(batch));
+                    }
+
+                    this.items.push(next);
+                    *this.weight = this.weight.saturating_add(weight);
+                }
+                Some(Err(err)) => {
+                    if this.items.is_empty() {
+                        break Some(Err(err));
+                    }
+
+                    // flush the partial batch first, the error is emitted on the next poll
+                    *this.error = Some(err);
+                    *this.weight = 0;
+                    break Some(Ok(mem::take(this.items)));
+                }
+                None => {
+                    *this.src_done = true;
+                    break if this.items.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(mem::take(this.items)))
+                    };
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let error = if self.error.is_some() { 1 } else { 0 };
+        if self.src_done {
+            (error, Some(error))
+        } else {
+            let (lower, upper) = weighted_size_hint(self.src.size_hint(), !self.items.is_empty());
+            (
+                lower.saturating_add(error),
+                upper.and_then(|upper| upper.checked_add(error)),
+            )
+        }
+    }
+}
+
+impl<S, F> FusedStream for TryChunksWeighted<S, F>
+where
+    S: TryStream,
+    F: FnMut(&S::Ok) -> usize,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.error.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, F, Item, E> Sink<Item> for TryChunksWeighted<S, F>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F> TryChunksWeighted<S, F>
+where
+    S: TryStream,
+    F: FnMut(&S::Ok) -> usize,
+{
+    pub(crate) fn new(src: S, max_weight: usize, weight_fn: F) -> Self {
+        Self {
+            src,
+            weight_fn,
+            max_weight,
+            items: Vec::new(),
+            weight: 0,
+            error: None,
+            src_done: false,
+        }
+    }
+}
+
+fn weighted_size_hint(
+    (src_lower, src_upper): (usize, Option<usize>),
+    has_items: bool,
+) -> (usize, Option<usize>) {
+    let buffered = if has_items { 1 } else { 0 };
+    // everything could fit in one batch, or every item could be over-weight
+    let lower = if src_lower > 0 || has_items { 1 } else { 0 };
+    (lower, src_upper.and_then(|upper| upper.checked_add(buffered)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunksWeighted, TryChunksWeighted};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_chunks_weighted() {
+        let src = futures::stream::iter(vec!["ab", "cd", "efg", "h", "ijklmnop", "q", "rstu"]);
+        let chunks = ChunksWeighted::new(src, 5, |s: &&str| s.len());
+        assert_eq!(
+            block_on(chunks.collect::<Vec<_>>()),
+            vec![
+                vec!["ab", "cd"],
+                vec!["efg", "h"],
+                vec!["ijklmnop"],
+                vec!["q", "rstu"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunks_weighted_over_weight_first() {
+        let src = futures::stream::iter(vec![10, 1, 1, 10]);
+        let chunks = ChunksWeighted::new(src, 3, |n: &usize| *n);
+        assert_eq!(
+            block_on(chunks.collect::<Vec<_>>()),
+            vec![vec![10], vec![1, 1], vec![10]]
+        );
+    }
+
+    #[test]
+    fn test_try_chunks_weighted() {
+        let src = ScriptedStream::interleaved(vec![Ok(2), Ok(2), Err("oh no!"), Ok(4), Ok(1)]);
+        let mut chunks = TryChunksWeighted::new(src, 4, |n: &usize| *n);
+        assert_stream_yields(
+            &mut chunks,
+            vec![Ok(vec![2, 2]), Err("oh no!"), Ok(vec![4]), Ok(vec![1])],
+        );
+    }
+}
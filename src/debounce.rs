@@ -0,0 +1,346 @@
+use crate::op_prelude::*;
+use crate::time::{yield_now, Timer, POLL_BUDGET};
+use std::time::Duration;
+
+pin_project! {
+    /// Stream for the [`debounce`](super::JStreamExt::debounce) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Debounce<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        duration: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        pending: Option<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for Debounce<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.src_done {
+            return Poll::Ready(None);
+        }
+
+        // take everything the upstream has (up to the budget), restarting the quiet period if
+        // anything was received
+        let mut received = false;
+        let mut src_pending = false;
+        for _ in 0..POLL_BUDGET {
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    *this.pending = Some(next);
+                    received = true;
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                    return Poll::Ready(this.pending.take());
+                }
+                Poll::Pending => {
+                    src_pending = true;
+                    break;
+                }
+            }
+        }
+
+        if received {
+            this.delay.set(Some(this.timer.delay(*this.duration)));
+        }
+
+        if !src_pending {
+            return yield_now(cx);
+        }
+
+        if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+            ready!(delay.poll(cx));
+            this.delay.set(None);
+            return Poll::Ready(this.pending.take());
+        }
+
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        debounce_size_hint(self.src_done, self.pending.is_some(), self.src.size_hint())
+    }
+}
+
+impl<S, T> FusedStream for Debounce<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for Debounce<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> Debounce<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            duration,
+            delay: None,
+            pending: None,
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> Debounce<S, T2> {
+        Debounce::new(self.src, self.duration, timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_debounce`](super::JTryStreamExt::try_debounce) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryDebounce<S, T>
+    where
+        S: TryStream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        duration: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        pending: Option<S::Ok>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for TryDebounce<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    //noinspection DuplicatedCode
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.src_done {
+            return Poll::Ready(None);
+        }
+
+        // take everything the upstream has (up to the budget), restarting the quiet period if
+        // anything was received
+        let mut received = false;
+        let mut src_pending = false;
+        for _ in 0..POLL_BUDGET {
+            match this.src.as_mut().try_poll_next(cx) {
+                Poll::Ready(Some(Ok(next))) => {
+                    *this.pending = Some(next);
+                    received = true;
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    if received {
+                        this.delay.set(Some(this.timer.delay(*this.duration)));
+                    }
+                    return Poll::Ready(Some(Err(err)));
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                    return Poll::Ready(this.pending.take().map(Ok));
+                }
+                Poll::Pending => {
+                    src_pending = true;
+                    break;
+                }
+            }
+        }
+
+        if received {
+            this.delay.set(Some(this.timer.delay(*this.duration)));
+        }
+
+        if !src_pending {
+            return yield_now(cx);
+        }
+
+        if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+            ready!(delay.poll(cx));
+            this.delay.set(None);
+            return Poll::Ready(this.pending.take().map(Ok));
+        }
+
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        debounce_size_hint(self.src_done, self.pending.is_some(), self.src.size_hint())
+    }
+}
+
+impl<S, T> FusedStream for TryDebounce<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item, E> Sink<Item> for TryDebounce<S, T>
+where
+    S: TryStream + Sink<Item, Error=E>,
+    T: Timer,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, T> TryDebounce<S, T>
+where
+    S: TryStream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            duration,
+            delay: None,
+            pending: None,
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> TryDebounce<S, T2> {
+        TryDebounce::new(self.src, self.duration, timer)
+    }
+}
+
+fn debounce_size_hint(
+    src_done: bool,
+    has_pending: bool,
+    (src_lower, src_upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let pending = if has_pending { 1 } else { 0 };
+    if src_done {
+        (0, Some(0))
+    } else {
+        // any number of items can be collapsed into one, but at least one will come out
+        let lower = src_lower.saturating_add(pending).min(1);
+        (lower, src_upper.and_then(|upper| upper.checked_add(pending)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Debounce, TryDebounce};
+    use crate::test_util::TimedHarness;
+    use crate::JStreamExt;
+    use futures::StreamExt;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_debounce() {
+        let mut harness = TimedHarness::new();
+        let mut debounced = Debounce::new(
+            harness.source(),
+            Duration::from_millis(100),
+            harness.clock(),
+        );
+
+        harness.send("a");
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+        harness.advance(Duration::from_millis(50));
+        harness.send("b");
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+
+        // the quiet period restarted when "b" arrived
+        harness.advance(Duration::from_millis(99));
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+        let wakes = harness.wakes();
+        harness.advance(Duration::from_millis(1));
+        assert!(harness.wakes() > wakes);
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(Some("b")));
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+
+        // the last item is emitted right away when the upstream ends
+        harness.send("c");
+        harness.close();
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(Some("c")));
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_try_debounce_errors() {
+        let mut harness = TimedHarness::new();
+        let mut debounced = TryDebounce::new(
+            harness.source(),
+            Duration::from_millis(100),
+            harness.clock(),
+        );
+
+        harness.send(Ok("a"));
+        harness.send(Err("oh no!"));
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(Some(Err("oh no!"))));
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(Some(Ok("a"))));
+        harness.close();
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_debounce_with_timer() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::iter(vec![1, 2, 3]).chain(futures::stream::pending());
+        let mut debounced = src
+            .debounce(Duration::from_secs(1))
+            .with_timer(harness.clock());
+
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut debounced), Poll::Ready(Some(3)));
+    }
+
+    #[test]
+    fn test_debounce_always_ready_source() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::repeat(1);
+        let mut debounced = src.debounce(Duration::from_secs(1)).with_timer(harness.clock());
+
+        // the source never goes quiet, so nothing is emitted, but each poll still returns
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+        assert_eq!(harness.poll(&mut debounced), Poll::Pending);
+        assert_eq!(harness.wakes(), 2);
+    }
+}
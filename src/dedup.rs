@@ -1,7 +1,7 @@
 use crate::op_prelude::*;
 use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
-use std::hash::{BuildHasher, Hash, Hasher};
+use std::hash::{BuildHasher, Hash};
 
 pin_project! {
     /// Stream for the [`try_dedup`](super::ext::JTryStreamExt::try_dedup) method
@@ -9,7 +9,6 @@ pin_project! {
     pub struct TryDedupStream<S> {
         #[pin]
         src: S,
-        size_hint: (usize, Option<usize>),
         known: HashSet<u64>,
         hasher: RandomState,
     }
@@ -35,7 +34,8 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.size_hint
+        // every item could be a duplicate
+        (0, self.src.size_hint().1)
     }
 }
 
@@ -63,10 +63,8 @@ where
 {
     //noinspection DuplicatedCode
     pub(crate) fn new(src: S) -> Self {
-        let size_hint = src.size_hint();
         Self {
             src,
-            size_hint,
             hasher: RandomState::default(),
             known: HashSet::default(),
         }
@@ -79,7 +77,6 @@ pin_project! {
     pub struct DedupStream<S> {
         #[pin]
         src: S,
-        size_hint: (usize, Option<usize>),
         known: HashSet<u64>,
         hasher: RandomState,
     }
@@ -106,7 +103,8 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.size_hint
+        // every item could be a duplicate
+        (0, self.src.size_hint().1)
     }
 }
 
@@ -133,10 +131,8 @@ where
 {
     //noinspection DuplicatedCode
     pub(crate) fn new(src: S) -> Self {
-        let size_hint = src.size_hint();
         Self {
             src,
-            size_hint,
             hasher: RandomState::default(),
             known: HashSet::default(),
         }
@@ -147,16 +143,15 @@ fn hash<H>(hasher: &RandomState, value: &H) -> u64
 where
     H: Hash,
 {
-    let mut hasher = hasher.build_hasher();
-    value.hash(&mut hasher);
-    hasher.finish()
+    hasher.hash_one(value)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::TryDedupStream;
+    use super::{DedupStream, TryDedupStream};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
     use futures::executor::block_on;
-    use futures::TryStreamExt;
+    use futures::{Stream, StreamExt, TryStreamExt};
 
     #[test]
     fn test_dedup_simple() {
@@ -185,4 +180,34 @@ mod tests {
         assert_eq!(block_on(raised.try_next()), Ok(Some("abc z")));
         assert_eq!(block_on(raised.try_next()), Err(()));
     }
+
+    #[test]
+    fn test_dedup_size_hint() {
+        let mut raised = DedupStream::new(futures::stream::iter(vec!["a", "a", "b"]));
+        assert_eq!(raised.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(raised.next()), Some("a"));
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+        assert_eq!(block_on(raised.next()), Some("b"));
+        assert_eq!(raised.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_try_dedup_size_hint() {
+        let src: Vec<Result<&str, ()>> = vec![Ok("a"), Ok("a"), Err(())];
+        let mut raised = TryDedupStream::new(futures::stream::iter(src));
+        assert_eq!(raised.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("a")));
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+    }
+
+    #[test]
+    fn test_dedup_pending() {
+        let src = ScriptedStream::interleaved(vec!["a", "a", "b", "a"]);
+        let handle = src.handle();
+        assert_stream_yields(&mut DedupStream::new(src), vec!["a", "b"]);
+        assert_eq!(handle.polls_after_end(), 0);
+
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(()), Ok("a"), Err(()), Ok("b")]);
+        assert_stream_yields(&mut TryDedupStream::new(src), vec![Ok("a"), Err(()), Err(()), Ok("b")]);
+    }
 }
@@ -0,0 +1,330 @@
+use crate::op_prelude::*;
+use crate::time::{deadline_after, Timer};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+///
+/// Decides when each item of a [`DelayStream`] is emitted.
+///
+/// This is implemented by [`FixedDelay`], [`DelayBy`] and [`ReplayAt`], which are used by the
+/// [`delay`](super::JStreamExt::delay), [`delay_by`](super::JStreamExt::delay_by) and
+/// [`replay_at`](super::JStreamExt::replay_at) methods.
+///
+pub trait DelaySchedule<T> {
+    /// The time at which `item`, which was received at `received`, should be emitted
+    fn deadline(&mut self, item: &T, received: Instant) -> Instant;
+}
+
+/// The [`DelaySchedule`] used by the [`delay`](super::JStreamExt::delay) method
+#[derive(Debug, Clone, Copy)]
+pub struct FixedDelay(Duration);
+
+impl<T> DelaySchedule<T> for FixedDelay {
+    fn deadline(&mut self, _: &T, received: Instant) -> Instant {
+        deadline_after(received, self.0)
+    }
+}
+
+/// The [`DelaySchedule`] used by the [`delay_by`](super::JStreamExt::delay_by) method
+pub struct DelayBy<F>(F);
+
+impl<T, F> DelaySchedule<T> for DelayBy<F>
+where
+    F: FnMut(&T) -> Duration,
+{
+    fn deadline(&mut self, item: &T, received: Instant) -> Instant {
+        received + (self.0)(item)
+    }
+}
+
+/// The [`DelaySchedule`] used by the [`replay_at`](super::JStreamExt::replay_at) method
+pub struct ReplayAt<F>(F);
+
+impl<T, F> DelaySchedule<T> for ReplayAt<F>
+where
+    F: FnMut(&T) -> Instant,
+{
+    fn deadline(&mut self, item: &T, _: Instant) -> Instant {
+        (self.0)(item)
+    }
+}
+
+pin_project! {
+    ///
+    /// Stream for the [`delay`](super::JStreamExt::delay), [`delay_by`](super::JStreamExt::delay_by)
+    /// and [`replay_at`](super::JStreamExt::replay_at) methods
+    ///
+    #[must_use = "streams do nothing unless polled"]
+    pub struct DelayStream<S, T, D>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        schedule: D,
+        #[pin]
+        delay: Option<T::Delay>,
+        queue: VecDeque<(Instant, S::Item)>,
+        capacity: usize,
+        src_done: bool,
+    }
+}
+
+impl<S, T, D> Stream for DelayStream<S, T, D>
+where
+    S: Stream,
+    T: Timer,
+    D: DelaySchedule<S::Item>,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // items are scheduled as soon as they are received, so take everything the upstream has,
+        // unless the queue is full or the item at the front is already due
+        while !*this.src_done && this.queue.len() < *this.capacity {
+            let now = this.timer.now();
+            if this.queue.front().is_some_and(|(deadline, _)| *deadline <= now) {
+                break;
+            }
+
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    let deadline = this.schedule.deadline(&next, now);
+                    this.queue.push_back((deadline, next));
+                }
+                Poll::Ready(None) => *this.src_done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        let deadline = match this.queue.front() {
+            Some((deadline, _)) => *deadline,
+            None if *this.src_done => return Poll::Ready(None),
+            None => return Poll::Pending,
+        };
+
+        // an item is never emitted before the ones received ahead of it, even if it's due earlier
+        if this.timer.now() < deadline {
+            if this.delay.is_none() {
+                this.delay.set(Some(this.timer.delay_until(deadline)));
+            }
+            ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+        }
+
+        this.delay.set(None);
+        Poll::Ready(this.queue.pop_front().map(|(_, item)| item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let queued = self.queue.len();
+        if self.src_done {
+            (queued, Some(queued))
+        } else {
+            let (lower, upper) = self.src.size_hint();
+            (
+                lower.saturating_add(queued),
+                upper.and_then(|upper| upper.checked_add(queued)),
+            )
+        }
+    }
+}
+
+impl<S, T, D> FusedStream for DelayStream<S, T, D>
+where
+    S: Stream,
+    T: Timer,
+    D: DelaySchedule<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.queue.is_empty()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, D, Item> Sink<Item> for DelayStream<S, T, D>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T, D> DelayStream<S, T, D>
+where
+    S: Stream,
+    T: Timer,
+    D: DelaySchedule<S::Item>,
+{
+    fn new(src: S, schedule: D, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            schedule,
+            delay: None,
+            queue: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            src_done: false,
+        }
+    }
+
+    ///
+    /// The maximum number of items which are held at once. The default is `1024`, and a value of
+    /// `0` is treated as `1`.
+    ///
+    /// Once this many items are held, the source stream is not polled again until the item at the
+    /// front is emitted, so a source which is always ready can't make the queue grow without limit.
+    /// The delay of an item is counted from when it is taken from the source stream, so a full
+    /// queue delays the items behind it by more than their own delay.
+    ///
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> DelayStream<S, T2, D> {
+        DelayStream::new(self.src, self.schedule, timer).capacity(self.capacity)
+    }
+}
+
+impl<S, T> DelayStream<S, T, FixedDelay>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn fixed(src: S, duration: Duration, timer: T) -> Self {
+        Self::new(src, FixedDelay(duration), timer)
+    }
+}
+
+impl<S, T, F> DelayStream<S, T, DelayBy<F>>
+where
+    S: Stream,
+    T: Timer,
+    F: FnMut(&S::Item) -> Duration,
+{
+    pub(crate) fn delay_by(src: S, f: F, timer: T) -> Self {
+        Self::new(src, DelayBy(f), timer)
+    }
+}
+
+impl<S, T, F> DelayStream<S, T, ReplayAt<F>>
+where
+    S: Stream,
+    T: Timer,
+    F: FnMut(&S::Item) -> Instant,
+{
+    pub(crate) fn replay_at(src: S, f: F, timer: T) -> Self {
+        Self::new(src, ReplayAt(f), timer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DelayStream;
+    use crate::test_util::TimedHarness;
+    use crate::time::Clock;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_delay() {
+        let mut harness = TimedHarness::new();
+        let mut delayed = DelayStream::fixed(
+            harness.source(),
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        harness.send(1);
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        harness.advance(Duration::from_millis(300));
+        harness.send(2);
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+
+        // every item keeps its spacing
+        harness.advance(Duration::from_millis(700));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        harness.advance(Duration::from_millis(299));
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(2)));
+
+        // items which are already queued are still emitted after the source ends
+        harness.send(3);
+        harness.close();
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_delay_by_preserves_order() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::iter(vec![3, 1, 2]);
+        let mut delayed =
+            DelayStream::delay_by(src, |n: &u64| Duration::from_secs(*n), harness.clock());
+
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        harness.advance(Duration::from_secs(3));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(2)));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_replay_at() {
+        let harness: TimedHarness = TimedHarness::new();
+        let start = harness.clock().now();
+        let src = futures::stream::iter(vec![0, 2, 5]);
+        let mut replayed = DelayStream::replay_at(
+            src,
+            move |n: &u64| start + Duration::from_secs(*n),
+            harness.clock(),
+        );
+
+        assert_eq!(harness.poll(&mut replayed), Poll::Ready(Some(0)));
+        assert_eq!(harness.poll(&mut replayed), Poll::Pending);
+        assert_eq!(harness.clock().next_deadline(), Some(start + Duration::from_secs(2)));
+        harness.clock().advance_to_next();
+        assert_eq!(harness.poll(&mut replayed), Poll::Ready(Some(2)));
+        assert_eq!(harness.poll(&mut replayed), Poll::Pending);
+        harness.clock().advance_to_next();
+        assert_eq!(harness.clock().now(), start + Duration::from_secs(5));
+        assert_eq!(harness.poll(&mut replayed), Poll::Ready(Some(5)));
+        assert_eq!(harness.poll(&mut replayed), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_delay_always_ready_source() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::repeat(7);
+        let mut delayed =
+            DelayStream::fixed(src, Duration::from_secs(1), harness.clock()).capacity(3);
+
+        // the queue stops growing once it is full, instead of draining the source forever
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        assert_eq!(delayed.queue.len(), 3);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(7)));
+
+        // items which are already due go out before any more are taken
+        assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some(7)));
+        assert_eq!(delayed.queue.len(), 1);
+
+        // the default capacity also bounds it
+        let src = futures::stream::iter(0..);
+        let mut delayed = DelayStream::fixed(src, Duration::from_secs(1), harness.clock());
+        assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+        assert_eq!(delayed.queue.len(), super::DEFAULT_CAPACITY);
+    }
+}
@@ -0,0 +1,543 @@
+use crate::op_prelude::*;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+///
+/// A window of items emitted by an [`EventTimeWindows`] stream.
+///
+/// Covers the event times `start..end`, and holds the items which fell into that range, in
+/// timestamp order (items with equal timestamps keep the order they were received in).
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window<T> {
+    /// The (inclusive) start of the window
+    pub start: Duration,
+    /// The (exclusive) end of the window
+    pub end: Duration,
+    /// The items in this window
+    pub items: Vec<T>,
+}
+
+///
+/// Decides which event-time windows an item belongs to, and keeps track of the windows which are
+/// still open.
+///
+/// This is implemented by [`TumblingWindows`], [`SlidingWindows`] and [`SessionWindows`], which are
+/// created by the methods of [`EventTime`].
+///
+pub trait WindowAssigner<T> {
+    ///
+    /// Add the item to every window it belongs to, unless all of those windows end at or before
+    /// `watermark`, in which case the item is late and is given back as `Err(item)`.
+    ///
+    fn assign(&mut self, timestamp: Duration, item: T, watermark: Option<Duration>) -> Result<(), T>;
+
+    /// Remove and return the earliest window which ends at or before `watermark`, if any
+    fn pop_closed(&mut self, watermark: Duration) -> Option<Window<T>>;
+
+    /// Remove and return the earliest open window, regardless of the watermark
+    fn pop_earliest(&mut self) -> Option<Window<T>>;
+
+    /// The number of windows which are still open
+    fn open_windows(&self) -> usize;
+}
+
+///
+/// Builder returned by the [`event_time`](super::JStreamExt::event_time) method.
+///
+/// Choose how items are grouped into windows using [`tumbling`](EventTime::tumbling),
+/// [`sliding`](EventTime::sliding) or [`session`](EventTime::session).
+///
+#[must_use = "streams do nothing unless polled"]
+pub struct EventTime<S, F> {
+    src: S,
+    extractor: F,
+    max_out_of_orderness: Duration,
+}
+
+impl<S, F> EventTime<S, F>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> Duration,
+{
+    pub(crate) fn new(src: S, extractor: F, max_out_of_orderness: Duration) -> Self {
+        Self {
+            src,
+            extractor,
+            max_out_of_orderness,
+        }
+    }
+
+    ///
+    /// Group items into fixed, non-overlapping windows of `size`, aligned to multiples of `size`.
+    ///
+    pub fn tumbling(self, size: Duration) -> EventTimeWindows<S, F, TumblingWindows<S::Item>> {
+        self.windows(TumblingWindows {
+            size: size.max(Duration::from_nanos(1)),
+            open: BTreeMap::new(),
+        })
+    }
+
+    ///
+    /// Group items into windows of `size` which start at every multiple of `step`. An item is
+    /// put into every window which covers its timestamp, so `S::Item` must be `Clone`.
+    ///
+    /// If `step` is larger than `size`, then items which fall between windows are dropped (they
+    /// are not considered late).
+    ///
+    pub fn sliding(
+        self,
+        size: Duration,
+        step: Duration,
+    ) -> EventTimeWindows<S, F, SlidingWindows<S::Item>>
+    where
+        S::Item: Clone,
+    {
+        self.windows(SlidingWindows {
+            size,
+            step: step.max(Duration::from_nanos(1)),
+            open: BTreeMap::new(),
+        })
+    }
+
+    ///
+    /// Group items into sessions, where each item is within `gap` of another item in the same
+    /// session. A session covers the timestamps from its first item until `gap` after its last
+    /// item.
+    ///
+    pub fn session(self, gap: Duration) -> EventTimeWindows<S, F, SessionWindows<S::Item>> {
+        self.windows(SessionWindows {
+            gap,
+            open: Vec::new(),
+        })
+    }
+
+    ///
+    /// Group items into windows using some other [`WindowAssigner`].
+    ///
+    pub fn windows<W>(self, windows: W) -> EventTimeWindows<S, F, W>
+    where
+        W: WindowAssigner<S::Item>,
+    {
+        EventTimeWindows {
+            src: self.src,
+            extractor: self.extractor,
+            windows,
+            on_late: drop,
+            max_out_of_orderness: self.max_out_of_orderness,
+            watermark: None,
+            src_done: false,
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`event_time`](super::JStreamExt::event_time) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct EventTimeWindows<S, F, W, L = fn(<S as Stream>::Item)>
+    where
+        S: Stream,
+    {
+        #[pin]
+        src: S,
+        extractor: F,
+        windows: W,
+        on_late: L,
+        max_out_of_orderness: Duration,
+        watermark: Option<Duration>,
+        src_done: bool,
+    }
+}
+
+impl<S, F, W, L> Stream for EventTimeWindows<S, F, W, L>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> Duration,
+    W: WindowAssigner<S::Item>,
+    L: FnMut(S::Item),
+{
+    type Item = Window<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if let Some(watermark) = *this.watermark {
+                if let Some(window) = this.windows.pop_closed(watermark) {
+                    break Some(window);
+                }
+            }
+
+            if *this.src_done {
+                break this.windows.pop_earliest();
+            }
+
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    let timestamp = (this.extractor)(&next);
+                    if let Err(late) = this.windows.assign(timestamp, next, *this.watermark) {
+                        (this.on_late)(late);
+                        continue;
+                    }
+
+                    let watermark = timestamp
+                        .checked_sub(*this.max_out_of_orderness)
+                        .unwrap_or_default();
+                    if Some(watermark) > *this.watermark {
+                        *this.watermark = Some(watermark);
+                    }
+                }
+                // nothing can arrive late anymore, so everything is emitted
+                None => *this.src_done = true,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let open = self.windows.open_windows();
+        if self.src_done {
+            (open, Some(open))
+        } else {
+            // any item could be late, or open any number of windows
+            (0, None)
+        }
+    }
+}
+
+impl<S, F, W, L> FusedStream for EventTimeWindows<S, F, W, L>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> Duration,
+    W: WindowAssigner<S::Item>,
+    L: FnMut(S::Item),
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.windows.open_windows() == 0
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, F, W, L, Item> Sink<Item> for EventTimeWindows<S, F, W, L>
+where
+    S: Stream + Sink<Item>,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, F, W, L> EventTimeWindows<S, F, W, L>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> Duration,
+    W: WindowAssigner<S::Item>,
+    L: FnMut(S::Item),
+{
+    ///
+    /// Call `on_late` with every item which arrives after all of the windows it belongs to have
+    /// been emitted. By default, late items are dropped.
+    ///
+    pub fn on_late<L2>(self, on_late: L2) -> EventTimeWindows<S, F, W, L2>
+    where
+        L2: FnMut(S::Item),
+    {
+        EventTimeWindows {
+            src: self.src,
+            extractor: self.extractor,
+            windows: self.windows,
+            on_late,
+            max_out_of_orderness: self.max_out_of_orderness,
+            watermark: self.watermark,
+            src_done: self.src_done,
+        }
+    }
+
+    ///
+    /// The current watermark, which is the largest timestamp seen so far minus the maximum
+    /// out-of-orderness, or `None` if no items have been seen yet.
+    ///
+    pub fn watermark(&self) -> Option<Duration> {
+        self.watermark
+    }
+}
+
+/// The [`WindowAssigner`] used by [`EventTime::tumbling`]
+pub struct TumblingWindows<T> {
+    size: Duration,
+    open: BTreeMap<Duration, Vec<(Duration, T)>>,
+}
+
+impl<T> WindowAssigner<T> for TumblingWindows<T> {
+    fn assign(&mut self, timestamp: Duration, item: T, watermark: Option<Duration>) -> Result<(), T> {
+        let start = align(timestamp, self.size);
+        if is_closed(start + self.size, watermark) {
+            return Err(item);
+        }
+
+        self.open.entry(start).or_default().push((timestamp, item));
+        Ok(())
+    }
+
+    fn pop_closed(&mut self, watermark: Duration) -> Option<Window<T>> {
+        let start = *self.open.keys().next()?;
+        if start + self.size <= watermark {
+            self.pop_earliest()
+        } else {
+            None
+        }
+    }
+
+    fn pop_earliest(&mut self) -> Option<Window<T>> {
+        let (start, items) = pop_first(&mut self.open)?;
+        Some(close_window(start, start + self.size, items))
+    }
+
+    fn open_windows(&self) -> usize {
+        self.open.len()
+    }
+}
+
+/// The [`WindowAssigner`] used by [`EventTime::sliding`]
+pub struct SlidingWindows<T> {
+    size: Duration,
+    step: Duration,
+    open: BTreeMap<Duration, Vec<(Duration, T)>>,
+}
+
+impl<T> WindowAssigner<T> for SlidingWindows<T>
+where
+    T: Clone,
+{
+    fn assign(&mut self, timestamp: Duration, item: T, watermark: Option<Duration>) -> Result<(), T> {
+        // walk backwards from the latest window which starts at or before the timestamp
+        let mut starts = Vec::new();
+        let mut start = align(timestamp, self.step);
+        let mut covered = false;
+        while start + self.size > timestamp {
+            covered = true;
+            if !is_closed(start + self.size, watermark) {
+                starts.push(start);
+            }
+
+            match start.checked_sub(self.step) {
+                Some(earlier) => start = earlier,
+                None => break,
+            }
+        }
+
+        if !covered {
+            return Ok(());
+        }
+
+        let last = match starts.pop() {
+            Some(last) => last,
+            None => return Err(item),
+        };
+
+        for start in starts {
+            self.open
+                .entry(start)
+                .or_default()
+                .push((timestamp, item.clone()));
+        }
+        self.open.entry(last).or_default().push((timestamp, item));
+        Ok(())
+    }
+
+    fn pop_closed(&mut self, watermark: Duration) -> Option<Window<T>> {
+        let start = *self.open.keys().next()?;
+        if start + self.size <= watermark {
+            self.pop_earliest()
+        } else {
+            None
+        }
+    }
+
+    fn pop_earliest(&mut self) -> Option<Window<T>> {
+        let (start, items) = pop_first(&mut self.open)?;
+        Some(close_window(start, start + self.size, items))
+    }
+
+    fn open_windows(&self) -> usize {
+        self.open.len()
+    }
+}
+
+/// The [`WindowAssigner`] used by [`EventTime::session`]
+pub struct SessionWindows<T> {
+    gap: Duration,
+    // disjoint, and sorted by start (and so also by end)
+    open: Vec<Session<T>>,
+}
+
+struct Session<T> {
+    start: Duration,
+    end: Duration,
+    items: Vec<(Duration, T)>,
+}
+
+impl<T> WindowAssigner<T> for SessionWindows<T> {
+    fn assign(&mut self, timestamp: Duration, item: T, watermark: Option<Duration>) -> Result<(), T> {
+        let mut start = timestamp;
+        let mut end = timestamp + self.gap;
+        let first = self.open.iter().position(|session| session.end > start);
+        let overlapping = match first {
+            Some(first) => {
+                let count = self.open[first..]
+                    .iter()
+                    .take_while(|session| session.start < end)
+                    .count();
+                first..first + count
+            }
+            None => self.open.len()..self.open.len(),
+        };
+
+        if overlapping.is_empty() && is_closed(end, watermark) {
+            return Err(item);
+        }
+
+        // merge every session which overlaps this item into one
+        let at = overlapping.start;
+        let mut items = Vec::new();
+        for session in self.open.drain(overlapping) {
+            start = start.min(session.start);
+            end = end.max(session.end);
+            items.extend(session.items);
+        }
+        items.push((timestamp, item));
+        self.open.insert(at, Session { start, end, items });
+        Ok(())
+    }
+
+    fn pop_closed(&mut self, watermark: Duration) -> Option<Window<T>> {
+        if self.open.first()?.end <= watermark {
+            self.pop_earliest()
+        } else {
+            None
+        }
+    }
+
+    fn pop_earliest(&mut self) -> Option<Window<T>> {
+        if self.open.is_empty() {
+            return None;
+        }
+
+        let session = self.open.remove(0);
+        Some(close_window(session.start, session.end, session.items))
+    }
+
+    fn open_windows(&self) -> usize {
+        self.open.len()
+    }
+}
+
+fn is_closed(end: Duration, watermark: Option<Duration>) -> bool {
+    watermark.is_some_and(|watermark| end <= watermark)
+}
+
+// the latest multiple of `step` which is not after `timestamp`
+fn align(timestamp: Duration, step: Duration) -> Duration {
+    let offset = timestamp.as_nanos() % step.as_nanos();
+    timestamp - Duration::from_nanos(offset as u64)
+}
+
+fn pop_first<V>(map: &mut BTreeMap<Duration, V>) -> Option<(Duration, V)> {
+    let first = *map.keys().next()?;
+    map.remove(&first).map(|value| (first, value))
+}
+
+fn close_window<T>(start: Duration, end: Duration, mut items: Vec<(Duration, T)>) -> Window<T> {
+    items.sort_by_key(|(timestamp, _)| *timestamp);
+    Window {
+        start,
+        end,
+        items: items.into_iter().map(|(_, item)| item).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventTime, Window};
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn window(start: u64, end: u64, items: Vec<u64>) -> Window<u64> {
+        Window {
+            start: secs(start),
+            end: secs(end),
+            items,
+        }
+    }
+
+    #[test]
+    fn test_event_time_tumbling() {
+        let src = futures::stream::iter(vec![1, 3, 2, 5, 4, 11, 6, 1, 12]);
+        let mut late = Vec::new();
+        let windows = EventTime::new(src, |n: &u64| secs(*n), secs(2))
+            .tumbling(secs(5))
+            .on_late(|n| late.push(n));
+
+        assert_eq!(
+            block_on(windows.collect::<Vec<_>>()),
+            vec![
+                // emitted once 11 moves the watermark to 9
+                window(0, 5, vec![1, 2, 3, 4]),
+                window(5, 10, vec![5, 6]),
+                window(10, 15, vec![11, 12]),
+            ]
+        );
+        assert_eq!(late, vec![1]);
+    }
+
+    #[test]
+    fn test_event_time_watermark() {
+        let src = futures::stream::iter(vec![1, 7, 4, 8, 9]);
+        let mut windows = EventTime::new(src, |n: &u64| secs(*n), secs(3)).tumbling(secs(5));
+        assert_eq!(windows.watermark(), None);
+
+        // 7 moves the watermark to 4, so 4 still fits, and 8 moves it to 5 which closes [0, 5)
+        assert_eq!(block_on(windows.next()), Some(window(0, 5, vec![1, 4])));
+        assert_eq!(windows.watermark(), Some(secs(5)));
+        assert_eq!(block_on(windows.next()), Some(window(5, 10, vec![7, 8, 9])));
+        assert_eq!(block_on(windows.next()), None);
+    }
+
+    #[test]
+    fn test_event_time_sliding() {
+        let src = futures::stream::iter(vec![1, 3, 5, 10]);
+        let windows = EventTime::new(src, |n: &u64| secs(*n), secs(0)).sliding(secs(4), secs(2));
+
+        assert_eq!(
+            block_on(windows.collect::<Vec<_>>()),
+            vec![
+                window(0, 4, vec![1, 3]),
+                window(2, 6, vec![3, 5]),
+                window(4, 8, vec![5]),
+                window(8, 12, vec![10]),
+                window(10, 14, vec![10]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_event_time_session() {
+        let src = futures::stream::iter(vec![1, 2, 6, 10, 4, 3, 20, 11, 5]);
+        let mut late = Vec::new();
+        let windows = EventTime::new(src, |n: &u64| secs(*n), secs(8))
+            .session(secs(3))
+            .on_late(|n| late.push(n));
+
+        assert_eq!(
+            block_on(windows.collect::<Vec<_>>()),
+            vec![
+                // 4 bridges the gap between the sessions of 2 and 6, merging them into one
+                window(1, 9, vec![1, 2, 3, 4, 6]),
+                window(10, 14, vec![10, 11]),
+                window(20, 23, vec![20]),
+            ]
+        );
+        assert_eq!(late, vec![5]);
+    }
+}
@@ -1,7 +1,10 @@
 use crate::ops::*;
+use crate::time::DefaultTimer;
 use futures::stream::FusedStream;
 use futures::{Future, Stream, TryFuture, TryStream};
+use std::cmp::Ordering;
 use std::hash::Hash;
+use std::time::{Duration, Instant};
 
 ///
 /// Extensions to the [`TryStream`](futures::TryStream) type which aren't already covered by the
@@ -56,6 +59,127 @@ pub trait JTryStreamExt: TryStream + Sized {
         TryFilterMapOk::new(self, predicate)
     }
 
+    ///
+    /// Same as [`try_filter_map_ok`](crate::JTryStreamExt::try_filter_map_ok), except the function
+    /// may fail by returning `Err(Self::Error)`.
+    ///
+    /// The function returns `Result<Option<R>, Self::Error>`. `Ok(Some(r))` is emitted as `Ok(r)`,
+    /// `Ok(None)` is skipped, and `Err(err)` is emitted as `Err(err)`, in the same way that errors
+    /// from the current stream are passed straight-through.
+    ///
+    fn try_filter_map_ok_fallible<F, R>(self, predicate: F) -> TryFilterMapOkFallible<Self, F, R>
+    where
+        F: FnMut(Self::Ok) -> Result<Option<R>, Self::Error>,
+    {
+        TryFilterMapOkFallible::new(self, predicate)
+    }
+
+    ///
+    /// Same as [`try_filter_map_ok`](crate::JTryStreamExt::try_filter_map_ok), except the function
+    /// returns a `Future<Output=Option<R>>`, which allows you to do async work (such as a lookup)
+    /// while filtering.
+    ///
+    /// Only one future is run at a time. See
+    /// [`try_filter_map_ok_buffered`](crate::JTryStreamExt::try_filter_map_ok_buffered) if you
+    /// want to run several at once.
+    ///
+    fn try_filter_map_ok_async<F, Fut, R>(self, predicate: F) -> TryFilterMapOkAsync<Self, F, Fut>
+    where
+        F: FnMut(Self::Ok) -> Fut,
+        Fut: Future<Output = Option<R>>,
+    {
+        TryFilterMapOkAsync::new(self, predicate)
+    }
+
+    ///
+    /// Same as [`try_filter_map_ok_async`](crate::JTryStreamExt::try_filter_map_ok_async), except
+    /// up to `n` of the futures are run concurrently.
+    ///
+    /// The output is emitted in the same order as the source stream, regardless of the order in
+    /// which the futures complete. Errors from the source stream also keep their position, so an
+    /// error is only emitted once every item before it has been emitted (or filtered out).
+    ///
+    /// A value of `0` for `n` is treated as `1`.
+    ///
+    fn try_filter_map_ok_buffered<F, Fut, R>(
+        self,
+        n: usize,
+        predicate: F,
+    ) -> TryFilterMapOkBuffered<Self, F, Fut>
+    where
+        F: FnMut(Self::Ok) -> Fut,
+        Fut: Future<Output = Option<R>>,
+    {
+        TryFilterMapOkBuffered::new(self, n, predicate)
+    }
+
+    ///
+    /// Expand each `Self::Ok` value of this stream into several values.
+    ///
+    /// If the current stream emits an `Ok(Self::Ok)` value, then the function passed to this method
+    /// is called to turn it into some [`IntoIterator`](std::iter::IntoIterator), and every item of
+    /// that iterator is emitted as `Ok(item)` before the next value is taken from this stream.
+    ///
+    /// Any errors emitted by the current stream are passed straight-through, in order.
+    ///
+    fn flat_map_ok<F, U>(self, f: F) -> FlatMapOk<Self, F, U>
+    where
+        F: FnMut(Self::Ok) -> U,
+        U: IntoIterator,
+    {
+        FlatMapOk::new(self, f)
+    }
+
+    ///
+    /// Same as [`flat_map_ok`](crate::JTryStreamExt::flat_map_ok), except the function returns some
+    /// [`TryStream`](futures::TryStream) (with the same `Error` type as this stream).
+    ///
+    /// Every item of the inner stream (including errors) is emitted before the next value is taken
+    /// from this stream. Errors from this stream and from the inner streams are all emitted inline,
+    /// in the order they are encountered, and do not stop either stream.
+    ///
+    fn flat_map_ok_stream<F, U>(self, f: F) -> FlatMapOkStream<Self, F, U>
+    where
+        F: FnMut(Self::Ok) -> U,
+        U: TryStream<Error = Self::Error>,
+    {
+        FlatMapOkStream::new(self, f)
+    }
+
+    ///
+    /// filter+map on the `Self::Error` value of this stream.
+    ///
+    /// This is the mirror image of [`try_filter_map_ok`](crate::JTryStreamExt::try_filter_map_ok).
+    /// If the current stream emits an `Err(Self::Error)` value, then the function passed to this
+    /// method is called. If it returns `None`, then the error is dropped, and if it returns
+    /// `Some(E)`, then `Err(E)` is emitted.
+    ///
+    /// Any `Ok(Self::Ok)` values are passed straight-through.
+    ///
+    fn filter_map_err<F, E>(self, predicate: F) -> FilterMapErr<Self, F, E>
+    where
+        F: FnMut(Self::Error) -> Option<E>,
+    {
+        FilterMapErr::new(self, predicate)
+    }
+
+    ///
+    /// Convert some errors emitted by this stream into substitute values.
+    ///
+    /// If the current stream emits an `Err(Self::Error)` value, then the function passed to this
+    /// method is called with a reference to the error. If it returns `Some(Self::Ok)`, then that
+    /// value is emitted in place of the error. If it returns `None`, then the error is emitted
+    /// unchanged.
+    ///
+    /// Any `Ok(Self::Ok)` values are passed straight-through.
+    ///
+    fn recover<F>(self, handler: F) -> Recover<Self, F>
+    where
+        F: FnMut(&Self::Error) -> Option<Self::Ok>,
+    {
+        Recover::new(self, handler)
+    }
+
     ///
     /// Given some stream where the `Self::Ok` type is `Hash`, then this method will allow you
     /// to "de-duplicate" that stream.
@@ -113,6 +237,183 @@ pub trait JTryStreamExt: TryStream + Sized {
     {
         TryFoldMut::new(self, initial, handler)
     }
+
+    ///
+    /// Same as [`try_fold_mut`](crate::JTryStreamExt::try_fold_mut), except errors do not abort the
+    /// fold.
+    ///
+    /// Every item is processed, and any `Err(Self::Error)` emitted by the source stream or returned
+    /// by the handler is collected into a `Vec`. Once the source stream completes, the returned
+    /// future emits `(T, Vec<Self::Error>)`, with the errors in the order they were encountered.
+    ///
+    /// This is useful for batch validation, where you want to report every failure instead of only
+    /// the first one.
+    ///
+    fn try_fold_mut_collect_errors<T, F, Fut>(
+        self,
+        initial: T,
+        handler: F,
+    ) -> TryFoldMutCollectErrors<Self, T, F, Fut>
+    where
+        Self: FusedStream,
+        F: FnMut(&mut T, Self::Ok) -> Fut,
+        Fut: TryFuture<Ok = (), Error = Self::Error>,
+    {
+        TryFoldMutCollectErrors::new(self, initial, handler)
+    }
+
+    ///
+    /// Turn this stream into a [`Future`](futures::Future) which consumes every item, and emits
+    /// all of the `Ok` values and all of the `Err` values as two separate `Vec`s
+    /// (`(Vec<Self::Ok>, Vec<Self::Error>)`).
+    ///
+    /// Unlike [`try_collect`](futures::TryStreamExt::try_collect), this does not stop at the first
+    /// error.
+    ///
+    fn partition_results(self) -> PartitionResults<Self> {
+        PartitionResults::new(self)
+    }
+
+    ///
+    /// Split this stream into two independently pollable streams: one which emits the `Self::Ok`
+    /// values, and one which emits the `Self::Error` values.
+    ///
+    /// This is useful when errors should be routed somewhere else (such as a dead-letter queue)
+    /// while successful items continue through the pipeline.
+    ///
+    /// Whichever half is polled pulls items from the source, and items meant for the other half
+    /// are buffered. Each buffer holds at most `capacity` items (at least 1). If one half isn't
+    /// polled and its buffer fills up, then the other half stops pulling from the source (returns
+    /// `Poll::Pending`) until room is made. If one half is dropped, the items meant for it are
+    /// discarded instead.
+    ///
+    /// Both halves end once the source ends and their buffers are drained.
+    ///
+    /// This is the streaming counterpart to
+    /// [`partition_results`](crate::JTryStreamExt::partition_results). It was first proposed
+    /// under that name too, but it was renamed to `split_results` (alongside
+    /// [`split_error`](crate::JTryStreamExt::split_error)) because `partition_results` already
+    /// names the future which collects both sides.
+    ///
+    fn split_results(self, capacity: usize) -> (SplitResultsOk<Self>, SplitResultsErr<Self>) {
+        split_results(self, capacity)
+    }
+
+    ///
+    /// Split this stream into a plain [`Stream`](futures::Stream) of the `Self::Ok` values, and a
+    /// [`Future`](futures::Future) which resolves to the error which ended the stream (if any).
+    ///
+    /// The returned stream ends as soon as the source emits an `Err(Self::Error)` item or completes.
+    /// Once that happens, the returned future resolves to `Some(Self::Error)` or `None`
+    /// respectively. If the stream is dropped before it ends, the future resolves to `None`.
+    ///
+    /// This allows you to write the "happy path" using plain [`StreamExt`](futures::StreamExt)
+    /// methods, and deal with the error separately.
+    ///
+    fn split_error(self) -> (SplitErrorStream<Self>, SplitErrorFuture<Self::Error>) {
+        split_error(self)
+    }
+
+    ///
+    /// Same as [`debounce`](crate::JStreamExt::debounce), except only `Ok(Self::Ok)` values are
+    /// debounced.
+    ///
+    /// Any `Err(Self::Error)` items are emitted immediately when they are encountered, and do not
+    /// restart the quiet period or discard the `Ok` value which is currently being held.
+    ///
+    fn try_debounce(self, duration: Duration) -> TryDebounce<Self, DefaultTimer> {
+        TryDebounce::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Same as [`timeout_each`](crate::JStreamExt::timeout_each), except the timeout is reported
+    /// by emitting `Err(on_timeout())` as an item of this stream, instead of wrapping every item in
+    /// another `Result`.
+    ///
+    /// Combine this with [`fuse_on_fail`](crate::JTryStreamExt::fuse_on_fail) to end the stream
+    /// on the first timeout (or on any other error).
+    ///
+    fn try_timeout_each<F>(
+        self,
+        duration: Duration,
+        on_timeout: F,
+    ) -> TryTimeoutEach<Self, DefaultTimer, F>
+    where
+        F: FnMut() -> Self::Error,
+    {
+        TryTimeoutEach::new(self, duration, on_timeout, DefaultTimer::default())
+    }
+
+    ///
+    /// Same as [`chunks_timeout`](crate::JStreamExt::chunks_timeout), except only the `Self::Ok`
+    /// values are batched, and the batches are emitted as `Ok(Vec<Self::Ok>)`.
+    ///
+    /// If an `Err(Self::Error)` item is encountered while a batch is being filled, then the partial
+    /// batch is emitted first, and the error is emitted right after it.
+    ///
+    fn try_chunks_timeout(
+        self,
+        max_items: usize,
+        max_delay: Duration,
+    ) -> TryChunksTimeout<Self, DefaultTimer> {
+        TryChunksTimeout::new(self, max_items, max_delay, DefaultTimer::default())
+    }
+
+    ///
+    /// Same as [`chunks_weighted`](crate::JStreamExt::chunks_weighted), except only the `Self::Ok`
+    /// values are batched, and the batches are emitted as `Ok(Vec<Self::Ok>)`.
+    ///
+    /// If an `Err(Self::Error)` item is encountered while a batch is being filled, then the partial
+    /// batch is emitted first, and the error is emitted right after it.
+    ///
+    fn try_chunks_weighted<F>(self, max_weight: usize, weight_fn: F) -> TryChunksWeighted<Self, F>
+    where
+        F: FnMut(&Self::Ok) -> usize,
+    {
+        TryChunksWeighted::new(self, max_weight, weight_fn)
+    }
+
+    ///
+    /// Same as [`pairwise`](crate::JStreamExt::pairwise), except only the `Self::Ok` values are
+    /// paired, and the pairs are emitted as `Ok((prev, cur))`.
+    ///
+    /// Any `Err(Self::Error)` items are emitted immediately, and reset the pairing, so the first
+    /// `Ok` value after an error is not paired with the one before it.
+    ///
+    fn try_pairwise(self) -> TryPairwise<Self>
+    where
+        Self::Ok: Clone,
+    {
+        TryPairwise::new(self)
+    }
+
+    ///
+    /// Same as [`windows`](crate::JStreamExt::windows), except only the `Self::Ok` values are
+    /// collected, and the windows are emitted as `Ok(Vec<Self::Ok>)`.
+    ///
+    /// Any `Err(Self::Error)` items are emitted immediately, and reset the window, so no window
+    /// contains items from both before and after an error.
+    ///
+    fn try_windows(self, n: usize) -> TryWindows<Self>
+    where
+        Self::Ok: Clone,
+    {
+        TryWindows::new(self, n)
+    }
+
+    ///
+    /// Same as [`merge_sorted_by`](crate::JStreamExt::merge_sorted_by), except only the
+    /// `Self::Ok` values are compared and merged.
+    ///
+    /// Any `Err(Self::Error)` items (from either stream) are emitted as soon as they are received.
+    ///
+    fn try_merge_sorted_by<O, F>(self, other: O, cmp: F) -> TryMergeSortedBy<Self, O, F>
+    where
+        O: TryStream<Ok = Self::Ok, Error = Self::Error>,
+        F: FnMut(&Self::Ok, &Self::Ok) -> Ordering,
+    {
+        TryMergeSortedBy::new(self, other, cmp)
+    }
 }
 
 impl<T> JTryStreamExt for T where T: TryStream + Sized {}
@@ -123,6 +424,9 @@ impl<T> JTryStreamExt for T where T: TryStream + Sized {}
 ///
 /// This is implemented using a blanket impl for all `Stream` implementors.
 ///
+/// The timed methods (such as [`debounce`](crate::JStreamExt::debounce)) use the
+/// [`DefaultTimer`](crate::time::DefaultTimer), see [Time](crate#time) to use a different one.
+///
 pub trait JStreamExt: Stream + Sized {
     ///
     /// Given some stream where the item is `Hash`, return a stream which only emits the unique
@@ -181,6 +485,302 @@ pub trait JStreamExt: Stream + Sized {
     fn nth(self, index: usize) -> StreamNth<Self> {
         StreamNth::new(self, index)
     }
+
+    ///
+    /// Only emit an item once `duration` has passed without the source stream emitting another
+    /// item.
+    ///
+    /// Every item emitted by this stream is held, and replaces any item which was already held.
+    /// Once the stream has been quiet for `duration`, the held item is emitted. When this stream
+    /// completes, the held item (if any) is emitted right away.
+    ///
+    fn debounce(self, duration: Duration) -> Debounce<Self, DefaultTimer> {
+        Debounce::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Limit this stream to `n` items per `per`, using a token bucket.
+    ///
+    /// The bucket holds up to `n` tokens (see [`burst`](crate::ops::RateLimit::burst) to change
+    /// this), and it starts full. A token is added every `per / n`, and each item emitted by the
+    /// returned stream takes one. If the bucket is empty, the next item is held back until a token
+    /// is available. Items are never dropped, and the source stream is not polled while an item is
+    /// being held back.
+    ///
+    /// A value of `0` for `n` is treated as `1`.
+    ///
+    fn rate_limit(self, n: usize, per: Duration) -> RateLimit<Self, DefaultTimer> {
+        RateLimit::new(self, n, per, DefaultTimer::default())
+    }
+
+    ///
+    /// Emit at most one item per `duration`, dropping the rest.
+    ///
+    /// An item received while no window is open opens a window of length `duration`, and is
+    /// emitted right away. Items received while the window is open are dropped. Use
+    /// [`leading`](crate::ops::Throttle::leading) and [`trailing`](crate::ops::Throttle::trailing)
+    /// on the returned stream to change whether the first item or the last item of each window is
+    /// emitted. At least one of them always stays enabled.
+    ///
+    fn throttle(self, duration: Duration) -> Throttle<Self, DefaultTimer> {
+        Throttle::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Emit the latest item received during each `period`, at the end of that period.
+    ///
+    /// Periods are aligned to the first time the returned stream is polled. Any other items
+    /// received during a period are dropped, and nothing is emitted for a period in which no items
+    /// were received. When this stream completes, the latest item (if any) is emitted right away.
+    ///
+    fn sample(self, period: Duration) -> Sample<Self, DefaultTimer> {
+        Sample::new(self, period, DefaultTimer::default())
+    }
+
+    ///
+    /// When an item is received, wait for `duration`, and then emit the latest item received in
+    /// that time.
+    ///
+    /// Unlike [`debounce`](crate::JStreamExt::debounce), new items don't restart the timer, so an
+    /// item is emitted at least every `duration` while this stream is busy. Unlike
+    /// [`throttle`](crate::JStreamExt::throttle), the item emitted is the last one of the period
+    /// rather than the first. When this stream completes, the latest item (if any) is emitted
+    /// right away.
+    ///
+    fn audit(self, duration: Duration) -> Audit<Self, DefaultTimer> {
+        Audit::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Detect a stalled upstream by emitting `Err(Elapsed)` whenever this stream takes longer than
+    /// `duration` to produce an item. Items are emitted as `Ok(Self::Item)`.
+    ///
+    /// The timer starts when the returned stream is polled and this stream isn't ready, and it is
+    /// restarted after every item and after every timeout, so a stream which stays stalled emits an
+    /// [`Elapsed`](crate::ops::Elapsed) error every `duration`. The returned stream ends when this
+    /// stream ends.
+    ///
+    /// The returned stream is a [`TryStream`](futures::TryStream), so
+    /// [`fuse_on_fail`](crate::JTryStreamExt::fuse_on_fail) can be used to end it on the first
+    /// timeout instead.
+    ///
+    fn timeout_each(self, duration: Duration) -> TimeoutEach<Self, DefaultTimer> {
+        TimeoutEach::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Emit an item produced by `generator` whenever this stream has been idle for `interval`,
+    /// such as a keepalive message.
+    ///
+    /// The idle time is counted from the first poll after the last item emitted by the returned
+    /// stream (including generated items), which is usually right after that item when the stream
+    /// is being consumed. A stream which stays idle gets an item every `interval`. Heartbeats stop
+    /// as soon as this stream ends.
+    ///
+    fn heartbeat<F>(self, interval: Duration, generator: F) -> Heartbeat<Self, DefaultTimer, F>
+    where
+        F: FnMut() -> Self::Item,
+    {
+        Heartbeat::new(self, interval, generator, DefaultTimer::default())
+    }
+
+    ///
+    /// Shift every item of this stream later by `duration`, keeping the spacing between them.
+    ///
+    /// Items are taken from this stream as soon as they are available, and each one is held until
+    /// `duration` after it was received. Up to 1024 items are held at once (see
+    /// [`capacity`](crate::ops::DelayStream::capacity)), after which this stream isn't polled
+    /// until the first held item is emitted. When this stream ends, the returned stream ends once
+    /// every held item is emitted.
+    ///
+    fn delay(self, duration: Duration) -> DelayStream<Self, DefaultTimer, FixedDelay> {
+        DelayStream::fixed(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Same as [`delay`](crate::JStreamExt::delay), except the delay of each item is computed by
+    /// `f`.
+    ///
+    /// The order of the items is preserved, so an item which is due before the item ahead of it is
+    /// emitted right after that item instead.
+    ///
+    fn delay_by<F>(self, f: F) -> DelayStream<Self, DefaultTimer, DelayBy<F>>
+    where
+        F: FnMut(&Self::Item) -> Duration,
+    {
+        DelayStream::delay_by(self, f, DefaultTimer::default())
+    }
+
+    ///
+    /// Same as [`delay`](crate::JStreamExt::delay), except each item is held until the time
+    /// returned by `f`, which makes it possible to replay recorded items with their original
+    /// spacing.
+    ///
+    /// The order of the items is preserved, so an item which is due before the item ahead of it is
+    /// emitted right after that item instead. Items which are due in the past are emitted right
+    /// away.
+    ///
+    fn replay_at<F>(self, f: F) -> DelayStream<Self, DefaultTimer, ReplayAt<F>>
+    where
+        F: FnMut(&Self::Item) -> Instant,
+    {
+        DelayStream::replay_at(self, f, DefaultTimer::default())
+    }
+
+    ///
+    /// Group the items of this stream into `Vec`s of up to `max_items` items, without waiting more
+    /// than `max_delay` for a batch to fill up.
+    ///
+    /// The timer starts when the first item of a batch is received. A batch is emitted as soon as
+    /// it is full, or once `max_delay` has passed, whichever comes first. When this stream ends, any
+    /// partial batch is emitted right away. Empty batches are never emitted.
+    ///
+    /// A value of `0` for `max_items` is treated as `1`.
+    ///
+    fn chunks_timeout(
+        self,
+        max_items: usize,
+        max_delay: Duration,
+    ) -> ChunksTimeout<Self, DefaultTimer> {
+        ChunksTimeout::new(self, max_items, max_delay, DefaultTimer::default())
+    }
+
+    ///
+    /// Group consecutive items of this stream into `Vec`s whose total weight is at most
+    /// `max_weight`, where the weight of each item is computed by `weight_fn` (such as the size of
+    /// the item in bytes).
+    ///
+    /// A batch is emitted once adding the next item would push it over `max_weight` (that item then
+    /// starts the next batch), or as soon as it reaches `max_weight` exactly. An item which weighs
+    /// more than `max_weight` on its own is emitted alone. When this stream ends, any partial batch
+    /// is emitted right away.
+    ///
+    fn chunks_weighted<F>(self, max_weight: usize, weight_fn: F) -> ChunksWeighted<Self, F>
+    where
+        F: FnMut(&Self::Item) -> usize,
+    {
+        ChunksWeighted::new(self, max_weight, weight_fn)
+    }
+
+    ///
+    /// Group the items of this stream into fixed, non-overlapping windows of `duration`, and emit
+    /// each window as a `Vec` once it closes.
+    ///
+    /// Windows are measured in processing time (the time at which items are received), and they
+    /// are aligned to the first time the returned stream is polled. Windows which don't receive any
+    /// items are skipped instead of being emitted as empty `Vec`s. When this stream ends, the open
+    /// window is emitted right away.
+    ///
+    fn window_tumbling(self, duration: Duration) -> WindowTumbling<Self, DefaultTimer> {
+        WindowTumbling::new(self, duration, DefaultTimer::default())
+    }
+
+    ///
+    /// Group the items of this stream into windows of `size` which start every `step`, and emit
+    /// each window as a `Vec` once it closes.
+    ///
+    /// If `step` is smaller than `size`, then the windows overlap and an item is emitted in every
+    /// window which covers the time it was received (which is why `Self::Item` must be `Clone`).
+    /// If `step` is larger than `size`, then items received between windows are dropped.
+    ///
+    /// Windows are measured in processing time, and they are aligned to the first time the
+    /// returned stream is polled. Windows which don't receive any items are skipped. When this
+    /// stream ends, every remaining window which holds an item is emitted right away.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero, since such a window could never hold any items.
+    ///
+    fn window_sliding(self, size: Duration, step: Duration) -> WindowSliding<Self, DefaultTimer>
+    where
+        Self::Item: Clone,
+    {
+        WindowSliding::new(self, size, step, DefaultTimer::default())
+    }
+
+    ///
+    /// Group the items of this stream into sessions, which are runs of items where each item is
+    /// received within `gap` of the one before it, and emit each session as a `Vec` once it closes.
+    ///
+    /// A session closes once `gap` has passed without any new items. When this stream ends, the
+    /// open session is emitted right away.
+    ///
+    fn window_session(self, gap: Duration) -> WindowSession<Self, DefaultTimer> {
+        WindowSession::new(self, gap, DefaultTimer::default())
+    }
+
+    ///
+    /// Group the items of this stream into windows by their event time (the time at which they
+    /// happened, according to `extractor`), instead of the time at which they were received.
+    ///
+    /// Timestamps are given as a `Duration` since some epoch of your choosing (such as the unix
+    /// epoch). Items may arrive out of order by up to `max_out_of_orderness`: the watermark is the
+    /// largest timestamp seen so far minus `max_out_of_orderness`, and a window is emitted as a
+    /// [`Window`](crate::ops::Window) once the watermark reaches its end. Items which arrive after
+    /// their windows were emitted are late, and are dropped or passed to
+    /// [`on_late`](crate::ops::EventTimeWindows::on_late). When this stream ends, every open window
+    /// is emitted right away.
+    ///
+    /// The returned builder is turned into a stream by choosing how items are grouped, using
+    /// [`tumbling`](crate::ops::EventTime::tumbling), [`sliding`](crate::ops::EventTime::sliding)
+    /// or [`session`](crate::ops::EventTime::session).
+    ///
+    /// Since this only depends on the timestamps of the items, it behaves the same whether the
+    /// stream is live or replayed.
+    ///
+    fn event_time<F>(self, extractor: F, max_out_of_orderness: Duration) -> EventTime<Self, F>
+    where
+        F: FnMut(&Self::Item) -> Duration,
+    {
+        EventTime::new(self, extractor, max_out_of_orderness)
+    }
+
+    ///
+    /// Emit every pair of consecutive items as `(prev, cur)`.
+    ///
+    /// Each item is emitted twice (once as `cur`, and then as `prev`), which is why `Self::Item`
+    /// must be `Clone`. A stream of `n` items gives `n - 1` pairs.
+    ///
+    fn pairwise(self) -> Pairwise<Self>
+    where
+        Self::Item: Clone,
+    {
+        Pairwise::new(self)
+    }
+
+    ///
+    /// Emit every run of `n` consecutive items as a `Vec`, similar to
+    /// [`slice::windows`](https://doc.rust-lang.org/std/primitive.slice.html#method.windows).
+    ///
+    /// The windows overlap, so each item is emitted in up to `n` of them, which is why
+    /// `Self::Item` must be `Clone`. Nothing is emitted if this stream has fewer than `n` items.
+    ///
+    /// A value of `0` for `n` is treated as `1`.
+    ///
+    fn windows(self, n: usize) -> Windows<Self>
+    where
+        Self::Item: Clone,
+    {
+        Windows::new(self, n)
+    }
+
+    ///
+    /// Merge this stream with `other`, where both are already sorted according to `cmp`, into one
+    /// stream which is sorted according to `cmp`.
+    ///
+    /// An item is only emitted once both streams have an item ready (or have ended), because the
+    /// next item could come from either of them. When `cmp` finds two items equal, the one from
+    /// this stream is emitted first.
+    ///
+    /// To merge more than two streams, use [`kmerge_by`](crate::kmerge_by).
+    ///
+    fn merge_sorted_by<O, F>(self, other: O, cmp: F) -> MergeSortedBy<Self, O, F>
+    where
+        O: Stream<Item = Self::Item>,
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        MergeSortedBy::new(self, other, cmp)
+    }
 }
 
 impl<T> JStreamExt for T where T: Stream + Sized {}
@@ -0,0 +1,187 @@
+use crate::op_prelude::*;
+
+pin_project! {
+    /// Stream for the [`filter_map_err`](super::JTryStreamExt::filter_map_err) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct FilterMapErr<S, F, E> {
+        #[pin]
+        src: S,
+        predicate: F,
+        _et: PhantomData<E>,
+    }
+}
+
+impl<S, F, E> Stream for FilterMapErr<S, F, E>
+where
+    S: TryStream,
+    F: FnMut(S::Error) -> Option<E>,
+{
+    type Item = Result<S::Ok, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => break Some(Ok(next)),
+                Some(Err(err)) => if let Some(out) = (this.predicate)(err) {
+                    break Some(Err(out));
+                }
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.src.size_hint().1)
+    }
+}
+
+impl<S, F, E> FusedStream for FilterMapErr<S, F, E>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(S::Error) -> Option<E>,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature="sink")]
+impl<S, F, E, Item, SE> Sink<Item> for FilterMapErr<S, F, E>
+where
+    S: TryStream + Sink<Item, Error=SE>,
+{
+    delegate_sink!(src, SE, Item);
+}
+
+impl<S, F, E> FilterMapErr<S, F, E>
+where
+    S: TryStream,
+    F: FnMut(S::Error) -> Option<E>,
+{
+    pub(crate) fn new(src: S, predicate: F) -> Self {
+        Self {
+            src,
+            predicate,
+            _et: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`recover`](super::JTryStreamExt::recover) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Recover<S, F> {
+        #[pin]
+        src: S,
+        handler: F,
+    }
+}
+
+impl<S, F> Stream for Recover<S, F>
+where
+    S: TryStream,
+    F: FnMut(&S::Error) -> Option<S::Ok>,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        Poll::Ready(match ready!(this.src.try_poll_next(cx)) {
+            Some(Err(err)) => Some((this.handler)(&err).ok_or(err)),
+            other => other,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.src.size_hint()
+    }
+}
+
+impl<S, F> FusedStream for Recover<S, F>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(&S::Error) -> Option<S::Ok>,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature="sink")]
+impl<S, F, Item, E> Sink<Item> for Recover<S, F>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F> Recover<S, F>
+where
+    S: TryStream,
+    F: FnMut(&S::Error) -> Option<S::Ok>,
+{
+    pub(crate) fn new(src: S, handler: F) -> Self {
+        Self { src, handler }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FilterMapErr, Recover};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::{Stream, TryStreamExt};
+
+    #[test]
+    fn test_filter_map_err() {
+        let src = futures::stream::iter(vec![Ok("a"), Err(404), Ok("b"), Err(500), Ok("c")]);
+        let mut raised = FilterMapErr::new(src, ignore_not_found);
+        assert_eq!(block_on(raised.try_next()), Ok(Some("a")));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("b")));
+        assert_eq!(block_on(raised.try_next()), Err("server error".to_owned()));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("c")));
+        assert_eq!(block_on(raised.try_next()), Ok(None));
+    }
+
+    #[test]
+    fn test_recover() {
+        let src = futures::stream::iter(vec![Ok("a"), Err(404), Err(500), Ok("b")]);
+        let mut raised = Recover::new(src, |err: &u16| if *err == 404 { Some("default") } else { None });
+        assert_eq!(block_on(raised.try_next()), Ok(Some("a")));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("default")));
+        assert_eq!(block_on(raised.try_next()), Err(500));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("b")));
+        assert_eq!(block_on(raised.try_next()), Ok(None));
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let src = futures::stream::iter(vec![Ok("a"), Err(404), Err(500)]);
+        let mut raised = FilterMapErr::new(src, ignore_not_found);
+        assert_eq!(raised.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("a")));
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+
+        let src = futures::stream::iter(vec![Ok("a"), Err(404)]);
+        let mut raised = Recover::new(src, |_: &u16| None);
+        assert_eq!(raised.size_hint(), (2, Some(2)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("a")));
+        assert_eq!(raised.size_hint(), (1, Some(1)));
+    }
+
+    #[test]
+    fn test_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(404), Err(500)]);
+        let expected = vec![Ok("a"), Err("server error".to_owned())];
+        assert_stream_yields(&mut FilterMapErr::new(src, ignore_not_found), expected);
+
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(404), Err(500)]);
+        let mut raised = Recover::new(src, |err: &u16| if *err == 404 { Some("b") } else { None });
+        assert_stream_yields(&mut raised, vec![Ok("a"), Ok("b"), Err(500)]);
+    }
+
+    fn ignore_not_found(code: u16) -> Option<String> {
+        if code == 404 {
+            None
+        } else {
+            Some("server error".to_owned())
+        }
+    }
+}
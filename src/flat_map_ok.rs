@@ -0,0 +1,211 @@
+use crate::op_prelude::*;
+
+pin_project! {
+    /// Stream for the [`flat_map_ok`](super::JTryStreamExt::flat_map_ok) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct FlatMapOk<S, F, U>
+    where
+        U: IntoIterator,
+    {
+        #[pin]
+        src: S,
+        f: F,
+        current: Option<U::IntoIter>,
+    }
+}
+
+impl<S, F, U> Stream for FlatMapOk<S, F, U>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> U,
+    U: IntoIterator,
+{
+    type Item = Result<U::Item, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if let Some(current) = this.current.as_mut() {
+                if let Some(next) = current.next() {
+                    break Some(Ok(next));
+                }
+                *this.current = None;
+            }
+
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => *this.current = Some((this.f)(next).into_iter()),
+                Some(Err(err)) => break Some(Err(err)),
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.current.as_ref().map_or((0, Some(0)), Iterator::size_hint);
+        if self.src.size_hint().1 == Some(0) {
+            (lower, upper)
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+impl<S, F, U> FusedStream for FlatMapOk<S, F, U>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(S::Ok) -> U,
+    U: IntoIterator,
+{
+    fn is_terminated(&self) -> bool {
+        self.current.is_none() && self.src.is_terminated()
+    }
+}
+
+#[cfg(feature="sink")]
+impl<S, F, U, Item, E> Sink<Item> for FlatMapOk<S, F, U>
+where
+    S: TryStream + Sink<Item, Error=E>,
+    U: IntoIterator,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F, U> FlatMapOk<S, F, U>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> U,
+    U: IntoIterator,
+{
+    pub(crate) fn new(src: S, f: F) -> Self {
+        Self { src, f, current: None }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`flat_map_ok_stream`](super::JTryStreamExt::flat_map_ok_stream) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct FlatMapOkStream<S, F, U> {
+        #[pin]
+        src: S,
+        f: F,
+        #[pin]
+        current: Option<U>,
+    }
+}
+
+impl<S, F, U> Stream for FlatMapOkStream<S, F, U>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> U,
+    U: TryStream<Error = S::Error>,
+{
+    type Item = Result<U::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if let Some(current) = this.current.as_mut().as_pin_mut() {
+                match ready!(current.try_poll_next(cx)) {
+                    Some(next) => break Some(next),
+                    None => this.current.set(None),
+                }
+            }
+
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => this.current.set(Some((this.f)(next))),
+                Some(Err(err)) => break Some(Err(err)),
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.current.as_ref().map_or((0, Some(0)), |current| current.size_hint());
+        if self.src.size_hint().1 == Some(0) {
+            (lower, upper)
+        } else {
+            (lower, None)
+        }
+    }
+}
+
+impl<S, F, U> FusedStream for FlatMapOkStream<S, F, U>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(S::Ok) -> U,
+    U: TryStream<Error = S::Error>,
+{
+    fn is_terminated(&self) -> bool {
+        self.current.is_none() && self.src.is_terminated()
+    }
+}
+
+#[cfg(feature="sink")]
+impl<S, F, U, Item, E> Sink<Item> for FlatMapOkStream<S, F, U>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F, U> FlatMapOkStream<S, F, U>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> U,
+    U: TryStream<Error = S::Error>,
+{
+    pub(crate) fn new(src: S, f: F) -> Self {
+        Self { src, f, current: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlatMapOk, FlatMapOkStream};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::{Stream, StreamExt};
+
+    #[test]
+    fn test_flat_map_ok() {
+        let src = futures::stream::iter(vec![Ok("a b"), Ok(""), Err(()), Ok("c")]);
+        let raised = FlatMapOk::new(src, |v: &str| v.split_whitespace().collect::<Vec<_>>());
+        let out: Vec<_> = block_on(raised.collect());
+        assert_eq!(out, vec![Ok("a"), Ok("b"), Err(()), Ok("c")]);
+    }
+
+    #[test]
+    fn test_flat_map_ok_stream() {
+        let src = futures::stream::iter(vec![Ok(2), Err("outer"), Ok(0), Ok(3)]);
+        let raised = FlatMapOkStream::new(src, |n: u32| {
+            futures::stream::iter((0..n).map(move |i| if i == 1 && n == 3 { Err("inner") } else { Ok(i) }))
+        });
+        let out: Vec<_> = block_on(raised.collect());
+        assert_eq!(out, vec![Ok(0), Ok(1), Err("outer"), Ok(0), Err("inner"), Ok(2)]);
+    }
+
+    #[test]
+    fn test_flat_map_ok_size_hint() {
+        let src = futures::stream::iter(vec![Ok::<_, ()>(vec![1, 2]), Ok(vec![3])]);
+        let mut raised = FlatMapOk::new(src, |v| v);
+        assert_eq!(raised.size_hint(), (0, None));
+        assert_eq!(block_on(raised.next()), Some(Ok(1)));
+        assert_eq!(raised.size_hint(), (1, None));
+        assert_eq!(block_on(raised.next()), Some(Ok(2)));
+        assert_eq!(block_on(raised.next()), Some(Ok(3)));
+        assert_eq!(raised.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_flat_map_ok_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok(vec![1, 2]), Err(()), Ok(vec![]), Ok(vec![3])]);
+        let mut raised = FlatMapOk::new(src, |v| v);
+        assert_stream_yields(&mut raised, vec![Ok(1), Ok(2), Err(()), Ok(3)]);
+
+        let src = ScriptedStream::interleaved(vec![Ok(vec![Ok(1), Err(())]), Err(()), Ok(vec![Ok(2)])]);
+        let handle = src.handle();
+        let mut raised = FlatMapOkStream::new(src, ScriptedStream::interleaved);
+        assert_stream_yields(&mut raised, vec![Ok(1), Err(()), Err(()), Ok(2)]);
+        assert_eq!(handle.polls_after_end(), 0);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::op_prelude::*;
 
-const POLL_AFTER_COMPLETED_MSG: &'static str = "call to poll after completed!";
+const POLL_AFTER_COMPLETED_MSG: &str = "call to poll after completed!";
 
 pin_project! {
     /// Future for the [`try_fold_mut`](super::JTryStreamExt::try_fold_mut) method
@@ -84,6 +84,90 @@ where
     delegate_sink!(upstream, E, Item);
 }
 
+pin_project! {
+    /// Future for the [`try_fold_mut_collect_errors`](super::JTryStreamExt::try_fold_mut_collect_errors) method
+    #[must_use = "futures do nothing unless polled"]
+    pub struct TryFoldMutCollectErrors<S, T, F, Fut>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        upstream: S,
+        #[pin]
+        pending_future: Option<Fut>,
+        state: Option<T>,
+        errors: Vec<S::Error>,
+        handler: F,
+    }
+}
+
+impl<S, T, F, Fut> TryFoldMutCollectErrors<S, T, F, Fut>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(&mut T, S::Ok) -> Fut,
+    Fut: TryFuture<Ok=(), Error=S::Error>,
+{
+    pub(crate) fn new(upstream: S, initial: T, handler: F) -> Self {
+        Self {
+            upstream,
+            pending_future: None,
+            state: Some(initial),
+            errors: Vec::new(),
+            handler,
+        }
+    }
+}
+
+impl<S, T, F, Fut> Future for TryFoldMutCollectErrors<S, T, F, Fut>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(&mut T, S::Ok) -> Fut,
+    Fut: TryFuture<Ok=(), Error=S::Error>,
+{
+    type Output = (T, Vec<S::Error>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            // poll future if we have one
+            if let Some(future) = this.pending_future.as_mut().as_pin_mut() {
+                let out = ready!(future.try_poll(cx));
+                this.pending_future.set(None);
+                if let Err(err) = out {
+                    this.errors.push(err);
+                }
+            }
+
+            // poll upstream
+            match ready!(this.upstream.as_mut().try_poll_next(cx)) {
+                // got something, no error
+                Some(Ok(next)) => {
+                    let state = this.state.as_mut().expect(POLL_AFTER_COMPLETED_MSG);
+                    let future = (this.handler)(state, next);
+                    this.pending_future.set(Some(future));
+                }
+                // got error, remember it and keep going
+                Some(Err(err)) => this.errors.push(err),
+                // upstream done
+                None => {
+                    let state = this.state.take().expect(POLL_AFTER_COMPLETED_MSG);
+                    break (state, std::mem::take(this.errors));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, F, Fut, Item, E> Sink<Item> for TryFoldMutCollectErrors<S, T, F, Fut>
+where
+    S: Sink<Item, Error=E> + TryStream + FusedStream,
+    F: FnMut(&mut T, S::Ok) -> Fut,
+    Fut: TryFuture<Ok=(), Error=<S as TryStream>::Error>,
+{
+    delegate_sink!(upstream, E, Item);
+}
+
 pin_project! {
     /// Future for the [`fold_mut`](super::JStreamExt::fold_mut) method
     #[must_use = "futures do nothing unless polled"]
@@ -157,4 +241,58 @@ where
     Fut: Future<Output=()>,
 {
     delegate_sink!(upstream, S::Error, Item);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FoldMut, TryFoldMut, TryFoldMutCollectErrors};
+    use crate::test_util::{run_future, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::future::ready;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_try_fold_mut_collect_errors() {
+        let src = futures::stream::iter(vec![Ok(1), Err("bad item"), Ok(2), Ok(3)]).fuse();
+        let folded = TryFoldMutCollectErrors::new(src, 0, |sum: &mut i32, next| {
+            let out = if next == 2 {
+                Err("bad handler")
+            } else {
+                *sum += next;
+                Ok(())
+            };
+            ready(out)
+        });
+
+        assert_eq!(block_on(folded), (4, vec!["bad item", "bad handler"]));
+    }
+
+    #[test]
+    fn test_fold_mut_pending() {
+        let src = ScriptedStream::interleaved(vec![1, 2, 3]);
+        let mut folded = Box::pin(FoldMut::new(src, 0, |sum: &mut i32, next| {
+            *sum += next;
+            ready(())
+        }));
+        assert_eq!(run_future(&mut folded), (6, 4));
+    }
+
+    #[test]
+    fn test_try_fold_mut_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok(1), Ok(2), Err("oh no!"), Ok(3)]);
+        let handle = src.handle();
+        let mut folded = Box::pin(TryFoldMut::new(src, 0, |sum: &mut i32, next| {
+            *sum += next;
+            ready(Ok(()))
+        }));
+        assert_eq!(run_future(&mut folded).0, Err("oh no!"));
+        assert!(!handle.is_finished());
+
+        let src = ScriptedStream::interleaved(vec![Ok(1), Err("oh no!"), Ok(3)]);
+        let mut folded = Box::pin(TryFoldMutCollectErrors::new(src, 0, |sum: &mut i32, next| {
+            *sum += next;
+            ready(Ok(()))
+        }));
+        assert_eq!(run_future(&mut folded).0, (4, vec!["oh no!"]));
+    }
+}
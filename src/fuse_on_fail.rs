@@ -44,7 +44,12 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.src.size_hint()
+        if self.fused || self.failed {
+            (0, Some(0))
+        } else {
+            // any item could be an error, which ends the stream
+            (0, self.src.size_hint().1)
+        }
     }
 }
 
@@ -74,8 +79,10 @@ where
 #[cfg(test)]
 mod tests {
     use super::FuseOnFail;
+    use crate::test_util::{assert_marbles, assert_stream_yields, marbles, ScriptedStream};
     use futures::executor::block_on;
-    use futures::TryStreamExt;
+    use futures::stream::FusedStream;
+    use futures::{Stream, TryStreamExt};
 
     #[test]
     fn test_fuse_on_fail() {
@@ -111,4 +118,34 @@ mod tests {
         assert_eq!(block_on(lifted.try_next()), Ok(Some("hello")));
         assert_eq!(block_on(lifted.try_next()), Ok(None));
     }
+
+    #[test]
+    fn test_fuse_on_fail_size_hint() {
+        let src = futures::stream::iter(vec![Ok("a"), Err("oh no!"), Ok("shouldn't be there")]);
+        let mut lifted = FuseOnFail::new(src);
+
+        assert_eq!(lifted.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(lifted.try_next()), Ok(Some("a")));
+        assert_eq!(lifted.size_hint(), (0, Some(2)));
+        assert_eq!(block_on(lifted.try_next()), Err("oh no!"));
+        assert_eq!(lifted.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_fuse_on_fail_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err("oh no!"), Ok("shouldn't be there")]);
+        let handle = src.handle();
+        let mut lifted = FuseOnFail::new(src);
+
+        assert_stream_yields(&mut lifted, vec![Ok("a"), Err("oh no!")]);
+        assert!(lifted.is_terminated());
+        assert!(!handle.is_finished());
+    }
+
+    #[test]
+    fn test_fuse_on_fail_marbles() {
+        assert_marbles(&mut FuseOnFail::new(marbles("a-b--#-c|")), "a-b--#|");
+        assert_marbles(&mut FuseOnFail::new(marbles("-a-b-|")), "-a-b-|");
+        assert_marbles(&mut FuseOnFail::new(marbles("a-b")), "a-b");
+    }
 }
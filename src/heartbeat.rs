@@ -0,0 +1,121 @@
+use crate::op_prelude::*;
+use crate::time::{IdleEvent, IdleTimer, Timer};
+use std::time::Duration;
+
+pin_project! {
+    /// Stream for the [`heartbeat`](super::JStreamExt::heartbeat) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Heartbeat<S, T, F>
+    where
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        #[pin]
+        idle: IdleTimer<T>,
+        generator: F,
+    }
+}
+
+impl<S, T, F> Stream for Heartbeat<S, T, F>
+where
+    S: Stream,
+    T: Timer,
+    F: FnMut() -> S::Item,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let src = this.src;
+        let generator = this.generator;
+        let next = ready!(this.idle.poll_next(cx, |cx| src.poll_next(cx)));
+        Poll::Ready(next.map(|event| match event {
+            IdleEvent::Item(item) => item,
+            IdleEvent::Idle => generator(),
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.idle.size_hint(self.src.size_hint())
+    }
+}
+
+impl<S, T, F> FusedStream for Heartbeat<S, T, F>
+where
+    S: Stream,
+    T: Timer,
+    F: FnMut() -> S::Item,
+{
+    fn is_terminated(&self) -> bool {
+        self.idle.is_done()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, F, Item> Sink<Item> for Heartbeat<S, T, F>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T, F> Heartbeat<S, T, F>
+where
+    S: Stream,
+    T: Timer,
+    F: FnMut() -> S::Item,
+{
+    pub(crate) fn new(src: S, interval: Duration, generator: F, timer: T) -> Self {
+        Self {
+            src,
+            idle: IdleTimer::new(interval, timer),
+            generator,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> Heartbeat<S, T2, F> {
+        Heartbeat::new(self.src, self.idle.duration(), self.generator, timer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heartbeat;
+    use crate::test_util::TimedHarness;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_heartbeat() {
+        let mut harness = TimedHarness::new();
+        let mut beats = Heartbeat::new(
+            harness.source(),
+            Duration::from_secs(1),
+            || "ping",
+            harness.clock(),
+        );
+
+        assert_eq!(harness.poll(&mut beats), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut beats), Poll::Ready(Some("ping")));
+
+        // real items restart the idle timer
+        assert_eq!(harness.poll(&mut beats), Poll::Pending);
+        harness.advance(Duration::from_millis(900));
+        harness.send("data");
+        assert_eq!(harness.poll(&mut beats), Poll::Ready(Some("data")));
+        assert_eq!(harness.poll(&mut beats), Poll::Pending);
+        harness.advance(Duration::from_millis(900));
+        assert_eq!(harness.poll(&mut beats), Poll::Pending);
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.poll(&mut beats), Poll::Ready(Some("ping")));
+
+        // no more heartbeats once the source ends
+        harness.close();
+        assert_eq!(harness.poll(&mut beats), Poll::Ready(None));
+        assert_eq!(harness.clock().pending_delays(), 0);
+    }
+}
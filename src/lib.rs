@@ -24,6 +24,36 @@
 //!   item emitted by the source.
 //! * [`nth`](crate::JStreamExt::nth) - turns a stream into a future which emits an item after skipping
 //!   a specified number of preceding items.
+//! * [`debounce`](crate::JStreamExt::debounce) - only emit an item once the stream has been quiet
+//!   for some duration.
+//! * [`rate_limit`](crate::JStreamExt::rate_limit) - limit the stream to `n` items per some duration
+//!   using a token bucket, holding items back until they can be emitted.
+//! * [`throttle`](crate::JStreamExt::throttle) - emit at most one item per some duration, dropping
+//!   the rest.
+//! * [`sample`](crate::JStreamExt::sample) - emit the latest item received during each period.
+//! * [`audit`](crate::JStreamExt::audit) - emit the latest item some duration after an item is
+//!   received, without restarting the timer for later items.
+//! * [`timeout_each`](crate::JStreamExt::timeout_each) - emit an error whenever the stream takes
+//!   too long to produce an item.
+//! * [`heartbeat`](crate::JStreamExt::heartbeat) - emit a generated item whenever the stream has
+//!   been idle for some duration.
+//! * [`delay`](crate::JStreamExt::delay), [`delay_by`](crate::JStreamExt::delay_by) and
+//!   [`replay_at`](crate::JStreamExt::replay_at) - hold each item back until some later time,
+//!   preserving the order of the items.
+//! * [`chunks_timeout`](crate::JStreamExt::chunks_timeout) - group items into `Vec`s of up to some
+//!   size, emitting partial batches once they have waited long enough.
+//! * [`chunks_weighted`](crate::JStreamExt::chunks_weighted) - group items into `Vec`s whose total
+//!   weight (such as a size in bytes) stays under some limit.
+//! * [`window_tumbling`](crate::JStreamExt::window_tumbling),
+//!   [`window_sliding`](crate::JStreamExt::window_sliding) and
+//!   [`window_session`](crate::JStreamExt::window_session) - group items into `Vec`s by the time
+//!   they were received, using fixed, overlapping or gap-separated windows.
+//! * [`event_time`](crate::JStreamExt::event_time) - group items into windows by a timestamp
+//!   taken from each item, using watermarks to decide when windows are complete.
+//! * [`pairwise`](crate::JStreamExt::pairwise) - emit every pair of consecutive items.
+//! * [`windows`](crate::JStreamExt::windows) - emit every run of `n` consecutive items as a `Vec`.
+//! * [`merge_sorted_by`](crate::JStreamExt::merge_sorted_by) - merge two sorted streams into one
+//!   sorted stream.
 //!
 //! ## `TryStream` Extensions
 //!
@@ -37,6 +67,23 @@
 //! * [`try_filter_map_ok`](crate::JTryStreamExt::try_filter_map_ok) - similar to
 //!   [`filter_map`](futures::StreamExt::filter_map), except it allows you to filter-map on the `Ok`
 //!   part of the `TryStream`, and it emits any errors immediately when they are encountered.
+//! * [`try_filter_map_ok_fallible`](crate::JTryStreamExt::try_filter_map_ok_fallible) - same as
+//!   `try_filter_map_ok`, except the function returns `Result<Option<R>, Self::Error>`, and any
+//!   error it returns is emitted inline.
+//! * [`try_filter_map_ok_async`](crate::JTryStreamExt::try_filter_map_ok_async) - same as
+//!   `try_filter_map_ok`, except the function returns a `Future<Output=Option<R>>`.
+//! * [`try_filter_map_ok_buffered`](crate::JTryStreamExt::try_filter_map_ok_buffered) - same as
+//!   `try_filter_map_ok_async`, except up to `n` futures are run concurrently (the output order is
+//!   preserved).
+//! * [`flat_map_ok`](crate::JTryStreamExt::flat_map_ok) - expand each `Ok` value into the items
+//!   of some `IntoIterator`, emitting errors inline.
+//! * [`flat_map_ok_stream`](crate::JTryStreamExt::flat_map_ok_stream) - expand each `Ok` value into
+//!   the items of some inner `TryStream`, emitting errors from both streams inline.
+//! * [`filter_map_err`](crate::JTryStreamExt::filter_map_err) - the mirror image of
+//!   `try_filter_map_ok`, which filter-maps on the `Err` part of the `TryStream`, dropping any
+//!   errors which are mapped to `None`.
+//! * [`recover`](crate::JTryStreamExt::recover) - convert some errors into substitute `Ok` values,
+//!   passing the remaining errors straight-through.
 //! * [`try_dedup`](crate::JTryStreamExt::try_dedup) - remove duplicate items from a stream, but also
 //!   emit any errors immediately when they are seen.
 //! * [`fuse_on_fail`](crate::JTryStreamExt::fuse_on_fail) - if an error is seen, "fuse" the stream
@@ -47,6 +94,68 @@
 //!   [`try_fold`](futures::TryStreamExt::try_fold), but asks for a
 //!   `(&mut T, Self::Ok)` -> `Future<Output=Result<(), Self::Error>>` instead of a
 //!   `(T, Self::Ok)` -> `Future<Output=Result<T, Self::Error>>` folding function.
+//! * [`try_fold_mut_collect_errors`](crate::JTryStreamExt::try_fold_mut_collect_errors) - Similar
+//!   to [`try_fold_mut`](crate::JTryStreamExt::try_fold_mut), except every item is processed and
+//!   every error is collected, and the future emits `(T, Vec<Self::Error>)`.
+//! * [`partition_results`](crate::JTryStreamExt::partition_results) - turns the stream into a future
+//!   which emits every `Ok` value and every `Err` value as `(Vec<Self::Ok>, Vec<Self::Error>)`.
+//! * [`split_results`](crate::JTryStreamExt::split_results) - split the stream into two independently
+//!   pollable streams of the `Ok` values and the `Err` values, with a bounded buffer between them.
+//! * [`split_error`](crate::JTryStreamExt::split_error) - split the stream into a plain
+//!   [`Stream`](futures::Stream) of `Ok` values which ends at the first error, and a future which
+//!   resolves to that error (if any) once the stream ends.
+//! * [`try_debounce`](crate::JTryStreamExt::try_debounce) - same as `debounce`, except errors are
+//!   emitted immediately.
+//! * [`try_timeout_each`](crate::JTryStreamExt::try_timeout_each) - same as `timeout_each`, except
+//!   the timeout is emitted as an error of this stream's own error type.
+//! * [`try_chunks_timeout`](crate::JTryStreamExt::try_chunks_timeout) - same as `chunks_timeout`,
+//!   except errors are emitted inline, right after the partial batch before them.
+//! * [`try_chunks_weighted`](crate::JTryStreamExt::try_chunks_weighted) - same as
+//!   `chunks_weighted`, except errors are emitted inline, right after the partial batch before them.
+//! * [`try_pairwise`](crate::JTryStreamExt::try_pairwise) and
+//!   [`try_windows`](crate::JTryStreamExt::try_windows) - same as `pairwise` and `windows`, except
+//!   errors are emitted inline and reset the items being collected.
+//! * [`try_merge_sorted_by`](crate::JTryStreamExt::try_merge_sorted_by) - same as
+//!   `merge_sorted_by`, except only `Ok` values are merged, and errors are emitted as they arrive.
+//!
+//! ## Free Functions
+//!
+//! * [`retry_stream`](crate::retry_stream) - create a [`TryStream`](futures::TryStream) from a
+//!   factory function, which is called again (after waiting according to some
+//!   [`Backoff`](crate::ops::Backoff)) whenever the upstream emits an error.
+//! * [`retry_stream_resumable`](crate::retry_stream_resumable) - same as `retry_stream`, but the
+//!   factory is given a resume token so that consumption continues where it stopped.
+//! * [`kmerge_by`](crate::kmerge_by) - merge any number of streams, which are each sorted by some
+//!   key, into one stream sorted by that key.
+//! * [`try_kmerge_by`](crate::try_kmerge_by) - same as `kmerge_by`, except for
+//!   [`TryStream`](futures::TryStream)s, whose errors are emitted as they arrive.
+//!
+//! # Time
+//!
+//! The [`time`](crate::time) module provides a runtime-agnostic [`Clock`](crate::time::Clock) and
+//! [`Timer`](crate::time::Timer) abstraction, along with a manually advanced
+//! [`VirtualClock`](crate::time::VirtualClock) for testing time-based behavior without any real
+//! sleeping.
+//!
+//! Real timers are provided for tokio (with the `tokio` feature) and async-std (with the
+//! `async-std` feature), along with a pure-std [`ThreadTimer`](crate::time::ThreadTimer) which is
+//! always available, and is the [`DefaultTimer`](crate::time::DefaultTimer) no matter which
+//! features are enabled.
+//!
+//! Every timed stream returned by this crate (such as the ones from
+//! [`debounce`](crate::JStreamExt::debounce) or [`timeout_each`](crate::JStreamExt::timeout_each))
+//! uses the `DefaultTimer`, and has a `with_timer` method which swaps it for any other
+//! [`Timer`](crate::time::Timer), such as a `TokioTimer` inside a tokio runtime or a
+//! `VirtualClock` in tests. `with_timer` should be called before the stream is polled.
+//!
+//! Durations which are too long to be added to the current time (such as `Duration::MAX`) don't
+//! panic. They are treated as a deadline so far in the future that it will never be reached.
+//!
+//! # Testing
+//!
+//! If the `test-util` feature is enabled, the [`test_util`](crate::test_util) module provides a
+//! scripted stream which interleaves `Poll::Pending` with items, a counting waker, and helpers which
+//! check that streams and futures propagate wake-ups correctly.
 //!
 
 #[macro_use]
@@ -115,8 +224,29 @@ op_mods! {
     try_filter_map_ok,
     nth,
     fold_mut,
+    retry,
+    split_error,
+    partition,
+    filter_map_err,
+    flat_map_ok,
+    debounce,
+    rate_limit,
+    throttle,
+    timeout_each,
+    chunks_timeout,
+    chunks_weighted,
+    window,
+    event_time,
+    pairwise,
+    sample,
+    heartbeat,
+    delay,
+    merge_sorted,
 }
 
+pub use merge_sorted::{kmerge_by, try_kmerge_by};
+pub use retry::{retry_stream, retry_stream_resumable};
+
 pub(crate) mod op_prelude {
     #[cfg(feature = "sink")]
     pub use futures::sink::Sink;
@@ -130,3 +260,8 @@ pub(crate) mod op_prelude {
 
 mod ext;
 pub use ext::*;
+
+pub mod time;
+
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
@@ -0,0 +1,597 @@
+use crate::op_prelude::*;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::convert::Infallible;
+
+pin_project! {
+    /// Stream for the [`merge_sorted_by`](super::JStreamExt::merge_sorted_by) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct MergeSortedBy<S, O, F>
+    where
+        S: Stream,
+    {
+        #[pin]
+        left: S,
+        #[pin]
+        right: O,
+        cmp: F,
+        heads: MergeHeads<S::Item>,
+    }
+}
+
+impl<S, O, F> Stream for MergeSortedBy<S, O, F>
+where
+    S: Stream,
+    O: Stream<Item = S::Item>,
+    F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let left = this.left;
+        let right = this.right;
+        this.heads
+            .poll_next(
+                cx,
+                this.cmp,
+                |cx| left.poll_next(cx).map(|next| next.map(Ok)),
+                |cx| right.poll_next(cx).map(|next| next.map(Ok)),
+            )
+            .map(|next| next.map(never_fails))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heads.size_hint(self.left.size_hint(), self.right.size_hint())
+    }
+}
+
+impl<S, O, F> FusedStream for MergeSortedBy<S, O, F>
+where
+    S: Stream,
+    O: Stream<Item = S::Item>,
+    F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    fn is_terminated(&self) -> bool {
+        self.heads.is_terminated()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, O, F, Item> Sink<Item> for MergeSortedBy<S, O, F>
+where
+    S: Stream + Sink<Item>,
+{
+    delegate_sink!(left, S::Error, Item);
+}
+
+impl<S, O, F> MergeSortedBy<S, O, F>
+where
+    S: Stream,
+    O: Stream<Item = S::Item>,
+    F: FnMut(&S::Item, &S::Item) -> Ordering,
+{
+    pub(crate) fn new(left: S, right: O, cmp: F) -> Self {
+        Self {
+            left,
+            right,
+            cmp,
+            heads: MergeHeads::new(),
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_merge_sorted_by`](super::JTryStreamExt::try_merge_sorted_by) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryMergeSortedBy<S, O, F>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        left: S,
+        #[pin]
+        right: O,
+        cmp: F,
+        heads: MergeHeads<S::Ok>,
+    }
+}
+
+impl<S, O, F> Stream for TryMergeSortedBy<S, O, F>
+where
+    S: TryStream,
+    O: TryStream<Ok = S::Ok, Error = S::Error>,
+    F: FnMut(&S::Ok, &S::Ok) -> Ordering,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let left = this.left;
+        let right = this.right;
+        this.heads.poll_next(
+            cx,
+            this.cmp,
+            |cx| left.try_poll_next(cx),
+            |cx| right.try_poll_next(cx),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heads.size_hint(self.left.size_hint(), self.right.size_hint())
+    }
+}
+
+impl<S, O, F> FusedStream for TryMergeSortedBy<S, O, F>
+where
+    S: TryStream,
+    O: TryStream<Ok = S::Ok, Error = S::Error>,
+    F: FnMut(&S::Ok, &S::Ok) -> Ordering,
+{
+    fn is_terminated(&self) -> bool {
+        self.heads.is_terminated()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, O, F, Item, E> Sink<Item> for TryMergeSortedBy<S, O, F>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(left, E, Item);
+}
+
+impl<S, O, F> TryMergeSortedBy<S, O, F>
+where
+    S: TryStream,
+    O: TryStream<Ok = S::Ok, Error = S::Error>,
+    F: FnMut(&S::Ok, &S::Ok) -> Ordering,
+{
+    pub(crate) fn new(left: S, right: O, cmp: F) -> Self {
+        Self {
+            left,
+            right,
+            cmp,
+            heads: MergeHeads::new(),
+        }
+    }
+}
+
+///
+/// Stream for the [`kmerge_by`](crate::kmerge_by) function
+///
+#[must_use = "streams do nothing unless polled"]
+pub struct KMergeBy<S, F, K>
+where
+    S: Stream,
+{
+    state: KMergeState<S, K, S::Item>,
+    key: F,
+}
+
+// the streams are boxed, and nothing else is pinned
+impl<S, F, K> Unpin for KMergeBy<S, F, K> where S: Stream {}
+
+impl<S, F, K> Stream for KMergeBy<S, F, K>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> K,
+    K: Ord,
+{
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        this.state
+            .poll_next(cx, &mut this.key, |src, cx| {
+                src.poll_next(cx).map(|next| next.map(Ok))
+            })
+            .map(|next| next.map(never_fails))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.state.size_hint()
+    }
+}
+
+impl<S, F, K> FusedStream for KMergeBy<S, F, K>
+where
+    S: Stream,
+    F: FnMut(&S::Item) -> K,
+    K: Ord,
+{
+    fn is_terminated(&self) -> bool {
+        self.state.is_terminated()
+    }
+}
+
+///
+/// Stream for the [`try_kmerge_by`](crate::try_kmerge_by) function
+///
+#[must_use = "streams do nothing unless polled"]
+pub struct TryKMergeBy<S, F, K>
+where
+    S: TryStream,
+{
+    state: KMergeState<S, K, S::Ok>,
+    key: F,
+}
+
+// the streams are boxed, and nothing else is pinned
+impl<S, F, K> Unpin for TryKMergeBy<S, F, K> where S: TryStream {}
+
+impl<S, F, K> Stream for TryKMergeBy<S, F, K>
+where
+    S: TryStream,
+    F: FnMut(&S::Ok) -> K,
+    K: Ord,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        this.state.poll_next(cx, &mut this.key, |src, cx| src.try_poll_next(cx))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.state.size_hint()
+    }
+}
+
+impl<S, F, K> FusedStream for TryKMergeBy<S, F, K>
+where
+    S: TryStream,
+    F: FnMut(&S::Ok) -> K,
+    K: Ord,
+{
+    fn is_terminated(&self) -> bool {
+        self.state.is_terminated()
+    }
+}
+
+///
+/// Merge any number of streams, which are each sorted by `key`, into one stream which is sorted by
+/// `key`.
+///
+/// The next item is picked using a binary heap which holds the next item of every stream, so each
+/// item costs `O(log n)` for `n` streams. An item is only emitted once every stream which hasn't
+/// ended has an item in the heap. Items with equal keys are emitted in the order of the streams
+/// they came from.
+///
+/// If the streams are not sorted, the output is not sorted either, but every item is still
+/// emitted.
+///
+pub fn kmerge_by<I, F, K>(streams: I, key: F) -> KMergeBy<I::Item, F, K>
+where
+    I: IntoIterator,
+    I::Item: Stream,
+    F: FnMut(&<I::Item as Stream>::Item) -> K,
+    K: Ord,
+{
+    KMergeBy {
+        state: KMergeState::new(streams),
+        key,
+    }
+}
+
+///
+/// Same as [`kmerge_by`](crate::kmerge_by), except the streams are
+/// [`TryStream`](futures::TryStream)s, and only their `Ok` values are merged.
+///
+/// Any `Err` item is emitted as soon as it is received from any of the streams, and the stream
+/// which emitted it keeps being merged afterwards.
+///
+pub fn try_kmerge_by<I, F, K>(streams: I, key: F) -> TryKMergeBy<I::Item, F, K>
+where
+    I: IntoIterator,
+    I::Item: TryStream,
+    F: FnMut(&<I::Item as TryStream>::Ok) -> K,
+    K: Ord,
+{
+    TryKMergeBy {
+        state: KMergeState::new(streams),
+        key,
+    }
+}
+
+// The next item of both sides of a two-way merge, and whether each side has ended
+struct MergeHeads<T> {
+    left: Option<T>,
+    right: Option<T>,
+    left_done: bool,
+    right_done: bool,
+}
+
+impl<T> MergeHeads<T> {
+    fn new() -> Self {
+        Self {
+            left: None,
+            right: None,
+            left_done: false,
+            right_done: false,
+        }
+    }
+
+    // Both heads are needed to know which item comes next, but errors are emitted right away
+    fn poll_next<E, F>(
+        &mut self,
+        cx: &mut Context<'_>,
+        cmp: &mut F,
+        poll_left: impl FnOnce(&mut Context<'_>) -> Poll<Option<Result<T, E>>>,
+        poll_right: impl FnOnce(&mut Context<'_>) -> Poll<Option<Result<T, E>>>,
+    ) -> Poll<Option<Result<T, E>>>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let left_pending = match fill_head(&mut self.left, &mut self.left_done, cx, poll_left) {
+            Ok(pending) => pending,
+            Err(err) => return Poll::Ready(Some(Err(err))),
+        };
+        let right_pending = match fill_head(&mut self.right, &mut self.right_done, cx, poll_right) {
+            Ok(pending) => pending,
+            Err(err) => return Poll::Ready(Some(Err(err))),
+        };
+
+        if left_pending || right_pending {
+            return Poll::Pending;
+        }
+
+        let next = match (self.left.as_ref(), self.right.as_ref()) {
+            // on a tie, the left item goes first
+            (Some(l), Some(r)) if cmp(l, r) == Ordering::Greater => self.right.take(),
+            (Some(_), _) => self.left.take(),
+            (None, _) => self.right.take(),
+        };
+        Poll::Ready(next.map(Ok))
+    }
+
+    fn size_hint(
+        &self,
+        left: (usize, Option<usize>),
+        right: (usize, Option<usize>),
+    ) -> (usize, Option<usize>) {
+        merge_size_hint(
+            head_size_hint(self.left_done, self.left.is_some(), left),
+            head_size_hint(self.right_done, self.right.is_some(), right),
+        )
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.left_done && self.right_done && self.left.is_none() && self.right.is_none()
+    }
+}
+
+// Poll for a new head, unless there already is one or the stream has ended. Gives whether the
+// stream is pending, or the error it emitted.
+fn fill_head<T, E>(
+    head: &mut Option<T>,
+    done: &mut bool,
+    cx: &mut Context<'_>,
+    poll_src: impl FnOnce(&mut Context<'_>) -> Poll<Option<Result<T, E>>>,
+) -> Result<bool, E> {
+    if head.is_none() && !*done {
+        match poll_src(cx) {
+            Poll::Ready(Some(Ok(next))) => *head = Some(next),
+            Poll::Ready(Some(Err(err))) => return Err(err),
+            Poll::Ready(None) => *done = true,
+            Poll::Pending => return Ok(true),
+        }
+    }
+
+    Ok(false)
+}
+
+// The streams of a k-way merge, and the heap which holds the next item of each of them
+struct KMergeState<S, K, T> {
+    streams: Vec<Pin<Box<S>>>,
+    heap: BinaryHeap<HeapEntry<K, T>>,
+    // the streams which don't have an item in the heap, and haven't ended yet
+    waiting: Vec<usize>,
+}
+
+impl<S, K, T> KMergeState<S, K, T>
+where
+    S: Stream,
+    K: Ord,
+{
+    fn new(streams: impl IntoIterator<Item = S>) -> Self {
+        let streams: Vec<_> = streams.into_iter().map(Box::pin).collect();
+        Self {
+            waiting: (0..streams.len()).collect(),
+            heap: BinaryHeap::with_capacity(streams.len()),
+            streams,
+        }
+    }
+
+    // Every stream needs an item in the heap (or to have ended) to know which item comes next, but
+    // errors are emitted right away
+    fn poll_next<E>(
+        &mut self,
+        cx: &mut Context<'_>,
+        key: &mut impl FnMut(&T) -> K,
+        mut poll_src: impl FnMut(Pin<&mut S>, &mut Context<'_>) -> Poll<Option<Result<T, E>>>,
+    ) -> Poll<Option<Result<T, E>>> {
+        let mut i = 0;
+        while i < self.waiting.len() {
+            let index = self.waiting[i];
+            match poll_src(self.streams[index].as_mut(), cx) {
+                Poll::Ready(Some(Ok(next))) => {
+                    self.heap.push(HeapEntry::new(key(&next), index, next));
+                    self.waiting.swap_remove(i);
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => {
+                    self.waiting.swap_remove(i);
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        if !self.waiting.is_empty() {
+            return Poll::Pending;
+        }
+
+        Poll::Ready(self.heap.pop().map(|entry| {
+            self.waiting.push(entry.index);
+            Ok(entry.item)
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // streams which have ended aren't asked again, so only the ones with an item in the heap
+        // (or which are still waiting for one) are counted
+        let heap = self.heap.len();
+        self.waiting
+            .iter()
+            .copied()
+            .chain(self.heap.iter().map(|entry| entry.index))
+            .map(|index| self.streams[index].size_hint())
+            .fold((heap, Some(heap)), merge_size_hint)
+    }
+
+    fn is_terminated(&self) -> bool {
+        self.waiting.is_empty() && self.heap.is_empty()
+    }
+}
+
+struct HeapEntry<K, T> {
+    key: K,
+    index: usize,
+    item: T,
+}
+
+impl<K, T> HeapEntry<K, T> {
+    fn new(key: K, index: usize, item: T) -> Self {
+        Self { key, index, item }
+    }
+}
+
+impl<K: Ord, T> PartialEq for HeapEntry<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, T> Eq for HeapEntry<K, T> {}
+
+impl<K: Ord, T> PartialOrd for HeapEntry<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> Ord for HeapEntry<K, T> {
+    // reversed, so that the BinaryHeap (a max-heap) pops the smallest key, and then the first stream
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .key
+            .cmp(&self.key)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+// the plain streams are merged as streams of Ok values which never fail
+fn never_fails<T>(result: Result<T, Infallible>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(never) => match never {},
+    }
+}
+
+fn head_size_hint(
+    done: bool,
+    has_head: bool,
+    (lower, upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let head = if has_head { 1 } else { 0 };
+    if done {
+        (head, Some(head))
+    } else {
+        (
+            lower.saturating_add(head),
+            upper.and_then(|upper| upper.checked_add(head)),
+        )
+    }
+}
+
+fn merge_size_hint(
+    (left_lower, left_upper): (usize, Option<usize>),
+    (right_lower, right_upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    (
+        left_lower.saturating_add(right_lower),
+        left_upper.and_then(|left| right_upper.and_then(|right| left.checked_add(right))),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kmerge_by, try_kmerge_by, MergeSortedBy, TryMergeSortedBy};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::{Stream, StreamExt};
+
+    #[test]
+    fn test_merge_sorted_by() {
+        let left = ScriptedStream::interleaved(vec![1, 4, 5, 9]);
+        let right = ScriptedStream::interleaved(vec![2, 3, 5, 10, 11]);
+        let mut merged = MergeSortedBy::new(left, right, |a: &i32, b: &i32| a.cmp(b));
+        assert_eq!(merged.size_hint(), (9, Some(9)));
+        assert_stream_yields(&mut merged, vec![1, 2, 3, 4, 5, 5, 9, 10, 11]);
+    }
+
+    #[test]
+    fn test_merge_sorted_by_ties_prefer_left() {
+        let left = futures::stream::iter(vec![(1, "left"), (2, "left")]);
+        let right = futures::stream::iter(vec![(1, "right"), (2, "right")]);
+        let merged = MergeSortedBy::new(left, right, |a: &(i32, &str), b: &(i32, &str)| a.0.cmp(&b.0));
+        assert_eq!(
+            block_on(merged.collect::<Vec<_>>()),
+            vec![(1, "left"), (1, "right"), (2, "left"), (2, "right")]
+        );
+    }
+
+    #[test]
+    fn test_try_merge_sorted_by() {
+        let left = ScriptedStream::interleaved(vec![Ok(1), Err("oh no!"), Ok(4)]);
+        let right = ScriptedStream::interleaved(vec![Ok(2), Ok(3)]);
+        let mut merged = TryMergeSortedBy::new(left, right, |a: &i32, b: &i32| a.cmp(b));
+        assert_stream_yields(&mut merged, vec![Ok(1), Err("oh no!"), Ok(2), Ok(3), Ok(4)]);
+    }
+
+    #[test]
+    fn test_kmerge_by() {
+        let streams = vec![
+            ScriptedStream::interleaved(vec![1, 7, 8]),
+            ScriptedStream::interleaved(vec![]),
+            ScriptedStream::interleaved(vec![2, 3, 9]),
+            ScriptedStream::interleaved(vec![0, 5]),
+        ];
+        let mut merged = kmerge_by(streams, |n: &i32| *n);
+        assert_eq!(merged.size_hint(), (8, Some(8)));
+        assert_stream_yields(&mut merged, vec![0, 1, 2, 3, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_kmerge_by_key() {
+        let streams = vec![
+            futures::stream::iter(vec!["c", "bb", "aaaa"]),
+            futures::stream::iter(vec!["zz", "yyy"]),
+        ];
+        let merged = kmerge_by(streams, |s: &&str| s.len());
+        assert_eq!(
+            block_on(merged.collect::<Vec<_>>()),
+            vec!["c", "bb", "zz", "yyy", "aaaa"]
+        );
+    }
+
+    #[test]
+    fn test_try_kmerge_by() {
+        let streams = vec![
+            ScriptedStream::interleaved(vec![Ok(1), Ok(4)]),
+            ScriptedStream::interleaved(vec![Ok(2), Err("oh no!"), Ok(3)]),
+        ];
+        let mut merged = try_kmerge_by(streams, |n: &i32| *n);
+        assert_stream_yields(&mut merged, vec![Ok(1), Ok(2), Err("oh no!"), Ok(3), Ok(4)]);
+    }
+}
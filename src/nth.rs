@@ -87,17 +87,20 @@ impl<S> Future for StreamNth<S>
 where
     S: Stream
 {
-    type Output = S::Item;
+    type Output = Option<S::Item>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let mut this = self.project();
         Poll::Ready(loop {
-            if let Some(next) = ready!(this.src.as_mut().poll_next(cx)) {
-                if *this.remaining == 0 {
-                    break next;
-                } else {
-                    this.remaining.sub_assign(1);
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    if *this.remaining == 0 {
+                        break Some(next);
+                    } else {
+                        this.remaining.sub_assign(1);
+                    }
                 }
+                None => break None,
             }
         })
     }
@@ -126,7 +129,8 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::TryStreamNth;
+    use super::{StreamNth, TryStreamNth};
+    use crate::test_util::{run_future, ScriptedStream};
     use futures::executor::block_on;
 
     #[test]
@@ -143,4 +147,36 @@ mod tests {
         let raised = TryStreamNth::first(src);
         assert_eq!(block_on(raised), Ok(None));
     }
+
+    #[test]
+    fn test_stream_nth_pending() {
+        let mut raised = StreamNth::new(ScriptedStream::interleaved(vec!["a", "b", "c"]), 1);
+        assert_eq!(run_future(&mut raised), (Some("b"), 2));
+    }
+
+    #[test]
+    fn test_stream_nth_too_short() {
+        let src = ScriptedStream::interleaved(vec!["a"]);
+        let handle = src.handle();
+        let mut raised = StreamNth::new(src, 1);
+        assert_eq!(run_future(&mut raised).0, None);
+        assert_eq!(handle.polls_after_end(), 0);
+    }
+
+    #[test]
+    fn test_try_stream_nth_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Ok("b"), Err(()), Ok("c")]);
+        let mut raised = TryStreamNth::new(src, 1);
+        assert_eq!(run_future(&mut raised), (Ok(Some("b")), 2));
+
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(()), Ok("c")]);
+        let mut raised = TryStreamNth::new(src, 2);
+        assert_eq!(run_future(&mut raised), (Err(()), 2));
+    }
+
+    #[test]
+    fn test_stream_nth_ends_early() {
+        let raised = StreamNth::new(futures::stream::iter(vec!["a", "b"]), 2);
+        assert_eq!(block_on(raised), None);
+    }
 }
@@ -0,0 +1,351 @@
+use crate::op_prelude::*;
+use std::collections::VecDeque;
+
+pin_project! {
+    /// Stream for the [`pairwise`](super::JStreamExt::pairwise) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Pairwise<S>
+    where
+        S: Stream,
+    {
+        #[pin]
+        src: S,
+        prev: Option<S::Item>,
+    }
+}
+
+impl<S> Stream for Pairwise<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    type Item = (S::Item, S::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    if let Some(prev) = this.prev.replace(next.clone()) {
+                        break Some((prev, next));
+                    }
+                }
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // the first item doesn't make a pair on its own
+        let missing = if self.prev.is_some() { 0 } else { 1 };
+        let (lower, upper) = self.src.size_hint();
+        (
+            lower.saturating_sub(missing),
+            upper.map(|upper| upper.saturating_sub(missing)),
+        )
+    }
+}
+
+impl<S> FusedStream for Pairwise<S>
+where
+    S: FusedStream,
+    S::Item: Clone,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item> Sink<Item> for Pairwise<S>
+where
+    S: Stream + Sink<Item>,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S> Pairwise<S>
+where
+    S: Stream,
+{
+    pub(crate) fn new(src: S) -> Self {
+        Self { src, prev: None }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_pairwise`](super::JTryStreamExt::try_pairwise) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryPairwise<S>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        src: S,
+        prev: Option<S::Ok>,
+    }
+}
+
+impl<S> Stream for TryPairwise<S>
+where
+    S: TryStream,
+    S::Ok: Clone,
+{
+    type Item = Result<(S::Ok, S::Ok), S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => {
+                    if let Some(prev) = this.prev.replace(next.clone()) {
+                        break Some(Ok((prev, next)));
+                    }
+                }
+                Some(Err(err)) => {
+                    // the item after an error has nothing to pair with
+                    this.prev.take();
+                    break Some(Err(err));
+                }
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // errors reset the pairing, so many of the items might never make a pair
+        (0, self.src.size_hint().1)
+    }
+}
+
+impl<S> FusedStream for TryPairwise<S>
+where
+    S: TryStream + FusedStream,
+    S::Ok: Clone,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item, E> Sink<Item> for TryPairwise<S>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S> TryPairwise<S>
+where
+    S: TryStream,
+{
+    pub(crate) fn new(src: S) -> Self {
+        Self { src, prev: None }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`windows`](super::JStreamExt::windows) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Windows<S>
+    where
+        S: Stream,
+    {
+        #[pin]
+        src: S,
+        size: usize,
+        items: VecDeque<S::Item>,
+    }
+}
+
+impl<S> Stream for Windows<S>
+where
+    S: Stream,
+    S::Item: Clone,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    if let Some(window) = push_window(this.items, *this.size, next) {
+                        break Some(window);
+                    }
+                }
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // the first `size - 1` items don't make a window on their own
+        let missing = self.size - 1 - self.items.len().min(self.size - 1);
+        let (lower, upper) = self.src.size_hint();
+        (
+            lower.saturating_sub(missing),
+            upper.map(|upper| upper.saturating_sub(missing)),
+        )
+    }
+}
+
+impl<S> FusedStream for Windows<S>
+where
+    S: FusedStream,
+    S::Item: Clone,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item> Sink<Item> for Windows<S>
+where
+    S: Stream + Sink<Item>,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S> Windows<S>
+where
+    S: Stream,
+{
+    pub(crate) fn new(src: S, size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            src,
+            size,
+            items: VecDeque::with_capacity(size),
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_windows`](super::JTryStreamExt::try_windows) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryWindows<S>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        src: S,
+        size: usize,
+        items: VecDeque<S::Ok>,
+    }
+}
+
+impl<S> Stream for TryWindows<S>
+where
+    S: TryStream,
+    S::Ok: Clone,
+{
+    type Item = Result<Vec<S::Ok>, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => {
+                    if let Some(window) = push_window(this.items, *this.size, next) {
+                        break Some(Ok(window));
+                    }
+                }
+                Some(Err(err)) => {
+                    // windows never span an error
+                    this.items.clear();
+                    break Some(Err(err));
+                }
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // errors reset the window, so many of the items might never fill one
+        (0, self.src.size_hint().1)
+    }
+}
+
+impl<S> FusedStream for TryWindows<S>
+where
+    S: TryStream + FusedStream,
+    S::Ok: Clone,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item, E> Sink<Item> for TryWindows<S>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S> TryWindows<S>
+where
+    S: TryStream,
+{
+    pub(crate) fn new(src: S, size: usize) -> Self {
+        let size = size.max(1);
+        Self {
+            src,
+            size,
+            items: VecDeque::with_capacity(size),
+        }
+    }
+}
+
+fn push_window<T: Clone>(items: &mut VecDeque<T>, size: usize, next: T) -> Option<Vec<T>> {
+    if items.len() == size {
+        items.pop_front();
+    }
+
+    items.push_back(next);
+    if items.len() == size {
+        Some(items.iter().cloned().collect())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pairwise, TryPairwise, TryWindows, Windows};
+    use crate::test_util::{assert_stream_yields, ScriptedStream};
+    use futures::Stream;
+
+    #[test]
+    fn test_pairwise() {
+        let mut pairs = Pairwise::new(ScriptedStream::interleaved(vec![1, 3, 6, 10]));
+        assert_eq!(pairs.size_hint(), (3, Some(3)));
+        assert_stream_yields(&mut pairs, vec![(1, 3), (3, 6), (6, 10)]);
+    }
+
+    #[test]
+    fn test_try_pairwise_resets_on_error() {
+        let src = ScriptedStream::interleaved(vec![Ok(1), Ok(2), Err("oh no!"), Ok(3), Ok(4)]);
+        let mut pairs = TryPairwise::new(src);
+        assert_stream_yields(&mut pairs, vec![Ok((1, 2)), Err("oh no!"), Ok((3, 4))]);
+    }
+
+    #[test]
+    fn test_windows() {
+        let mut windows = Windows::new(futures::stream::iter(1..=5), 3);
+        assert_eq!(windows.size_hint(), (3, Some(3)));
+        assert_stream_yields(&mut windows, vec![vec![1, 2, 3], vec![2, 3, 4], vec![3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_windows_too_short() {
+        let mut windows = Windows::new(ScriptedStream::interleaved(vec![1, 2]), 3);
+        assert_stream_yields(&mut windows, vec![]);
+    }
+
+    #[test]
+    fn test_try_windows_resets_on_error() {
+        let src = ScriptedStream::interleaved(vec![Ok(1), Ok(2), Ok(3), Err("oh no!"), Ok(4), Ok(5)]);
+        let mut windows = TryWindows::new(src, 2);
+        assert_stream_yields(
+            &mut windows,
+            vec![Ok(vec![1, 2]), Ok(vec![2, 3]), Err("oh no!"), Ok(vec![4, 5])],
+        );
+    }
+}
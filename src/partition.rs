@@ -0,0 +1,373 @@
+use crate::op_prelude::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::Waker;
+
+pin_project! {
+    /// Future for the [`partition_results`](super::JTryStreamExt::partition_results) method
+    #[must_use = "futures do nothing unless polled"]
+    pub struct PartitionResults<S>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        src: S,
+        oks: Vec<S::Ok>,
+        errs: Vec<S::Error>,
+    }
+}
+
+impl<S> Future for PartitionResults<S>
+where
+    S: TryStream,
+{
+    type Output = (Vec<S::Ok>, Vec<S::Error>);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => this.oks.push(next),
+                Some(Err(err)) => this.errs.push(err),
+                None => break (std::mem::take(this.oks), std::mem::take(this.errs)),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item, E> Sink<Item> for PartitionResults<S>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S> PartitionResults<S>
+where
+    S: TryStream,
+{
+    pub(crate) fn new(src: S) -> Self {
+        let (lower, _) = src.size_hint();
+        Self {
+            src,
+            oks: Vec::with_capacity(lower),
+            errs: Vec::new(),
+        }
+    }
+}
+
+///
+/// Stream of `Ok` values for the [`split_results`](super::JTryStreamExt::split_results) method
+///
+/// Dropping this stream does not stop the other half. Any `Ok` values seen after it is dropped
+/// are discarded.
+///
+#[must_use = "streams do nothing unless polled"]
+pub struct SplitResultsOk<S>
+where
+    S: TryStream,
+{
+    shared: Arc<Mutex<SplitResultsShared<S>>>,
+}
+
+///
+/// Stream of `Err` values for the [`split_results`](super::JTryStreamExt::split_results) method
+///
+/// Dropping this stream does not stop the other half. Any `Err` values seen after it is dropped
+/// are discarded.
+///
+#[must_use = "streams do nothing unless polled"]
+pub struct SplitResultsErr<S>
+where
+    S: TryStream,
+{
+    shared: Arc<Mutex<SplitResultsShared<S>>>,
+}
+
+struct SplitResultsShared<S>
+where
+    S: TryStream,
+{
+    src: Pin<Box<S>>,
+    capacity: usize,
+    done: bool,
+    oks: VecDeque<S::Ok>,
+    errs: VecDeque<S::Error>,
+    ok_waker: Option<Waker>,
+    err_waker: Option<Waker>,
+    ok_alive: bool,
+    err_alive: bool,
+}
+
+impl<S> SplitResultsShared<S>
+where
+    S: TryStream,
+{
+    fn poll_side(&mut self, cx: &mut Context<'_>, want_ok: bool) -> Poll<Option<Result<S::Ok, S::Error>>> {
+        loop {
+            let buffered = if want_ok {
+                self.oks.pop_front().map(Ok)
+            } else {
+                self.errs.pop_front().map(Err)
+            };
+
+            // there's space in our buffer now, so the other side may continue
+            if let Some(item) = buffered {
+                self.wake_other(want_ok);
+                return Poll::Ready(Some(item));
+            }
+
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            // the other side isn't keeping up, so wait for it to make some room
+            let other_full = if want_ok {
+                self.err_alive && self.errs.len() >= self.capacity
+            } else {
+                self.ok_alive && self.oks.len() >= self.capacity
+            };
+            if other_full {
+                self.register(cx, want_ok);
+                return Poll::Pending;
+            }
+
+            let next = match self.src.as_mut().try_poll_next(cx) {
+                Poll::Ready(next) => next,
+                Poll::Pending => {
+                    self.register(cx, want_ok);
+                    return Poll::Pending;
+                }
+            };
+
+            // the upstream only remembers the most recent waker, so the other side has to poll it
+            // again to get woken up properly
+            self.wake_other(want_ok);
+            match (next, want_ok) {
+                (Some(Ok(v)), true) => return Poll::Ready(Some(Ok(v))),
+                (Some(Err(err)), false) => return Poll::Ready(Some(Err(err))),
+                (Some(Ok(v)), false) => if self.ok_alive {
+                    self.oks.push_back(v);
+                }
+                (Some(Err(err)), true) => if self.err_alive {
+                    self.errs.push_back(err);
+                }
+                (None, _) => {
+                    self.done = true;
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+
+    fn register(&mut self, cx: &mut Context<'_>, want_ok: bool) {
+        let slot = if want_ok { &mut self.ok_waker } else { &mut self.err_waker };
+        *slot = Some(cx.waker().clone());
+    }
+
+    fn wake_other(&mut self, want_ok: bool) {
+        let slot = if want_ok { &mut self.err_waker } else { &mut self.ok_waker };
+        if let Some(waker) = slot.take() {
+            waker.wake();
+        }
+    }
+
+    fn size_hint(&self, buffered: usize) -> (usize, Option<usize>) {
+        if self.done {
+            (buffered, Some(buffered))
+        } else {
+            let upper = self.src.size_hint().1.and_then(|upper| upper.checked_add(buffered));
+            (buffered, upper)
+        }
+    }
+}
+
+fn lock<S>(shared: &Mutex<SplitResultsShared<S>>) -> MutexGuard<'_, SplitResultsShared<S>>
+where
+    S: TryStream,
+{
+    shared.lock().expect("split_results state poisoned")
+}
+
+impl<S> Stream for SplitResultsOk<S>
+where
+    S: TryStream,
+{
+    type Item = S::Ok;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(match ready!(lock(&self.shared).poll_side(cx, true)) {
+            Some(Ok(v)) => Some(v),
+            Some(Err(_)) => unreachable!("ok side received an error"),
+            None => None,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let shared = lock(&self.shared);
+        shared.size_hint(shared.oks.len())
+    }
+}
+
+impl<S> Stream for SplitResultsErr<S>
+where
+    S: TryStream,
+{
+    type Item = S::Error;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(match ready!(lock(&self.shared).poll_side(cx, false)) {
+            Some(Err(err)) => Some(err),
+            Some(Ok(_)) => unreachable!("err side received an ok value"),
+            None => None,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let shared = lock(&self.shared);
+        shared.size_hint(shared.errs.len())
+    }
+}
+
+impl<S> FusedStream for SplitResultsOk<S>
+where
+    S: TryStream,
+{
+    fn is_terminated(&self) -> bool {
+        let shared = lock(&self.shared);
+        shared.done && shared.oks.is_empty()
+    }
+}
+
+impl<S> FusedStream for SplitResultsErr<S>
+where
+    S: TryStream,
+{
+    fn is_terminated(&self) -> bool {
+        let shared = lock(&self.shared);
+        shared.done && shared.errs.is_empty()
+    }
+}
+
+impl<S> Drop for SplitResultsOk<S>
+where
+    S: TryStream,
+{
+    fn drop(&mut self) {
+        let mut shared = lock(&self.shared);
+        shared.ok_alive = false;
+        shared.oks.clear();
+        shared.wake_other(true);
+    }
+}
+
+impl<S> Drop for SplitResultsErr<S>
+where
+    S: TryStream,
+{
+    fn drop(&mut self) {
+        let mut shared = lock(&self.shared);
+        shared.err_alive = false;
+        shared.errs.clear();
+        shared.wake_other(false);
+    }
+}
+
+pub(crate) fn split_results<S>(src: S, capacity: usize) -> (SplitResultsOk<S>, SplitResultsErr<S>)
+where
+    S: TryStream,
+{
+    let shared = Arc::new(Mutex::new(SplitResultsShared {
+        src: Box::pin(src),
+        capacity: capacity.max(1),
+        done: false,
+        oks: VecDeque::new(),
+        errs: VecDeque::new(),
+        ok_waker: None,
+        err_waker: None,
+        ok_alive: true,
+        err_alive: true,
+    }));
+
+    let oks = SplitResultsOk { shared: shared.clone() };
+    (oks, SplitResultsErr { shared })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_results, PartitionResults};
+    use crate::test_util::{assert_stream_yields, run_future, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_partition_results() {
+        let src = futures::stream::iter(vec![Ok("a"), Err(1), Ok("b"), Err(2), Ok("c")]);
+        let raised = PartitionResults::new(src);
+        assert_eq!(block_on(raised), (vec!["a", "b", "c"], vec![1, 2]));
+    }
+
+    #[test]
+    fn test_partition_results_empty() {
+        let src = futures::stream::empty::<Result<(), ()>>();
+        assert_eq!(block_on(PartitionResults::new(src)), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_split_results() {
+        let src = futures::stream::iter(vec![Ok("a"), Err(1), Ok("b"), Err(2), Ok("c")]);
+        let (oks, errs) = split_results(src, 4);
+        let (oks, errs) = block_on(futures::future::join(oks.collect::<Vec<_>>(), errs.collect::<Vec<_>>()));
+        assert_eq!(oks, vec!["a", "b", "c"]);
+        assert_eq!(errs, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_split_results_backpressure() {
+        let src = futures::stream::iter(vec![Err(1), Err(2), Ok("a"), Err(3)]);
+        let (mut oks, mut errs) = split_results(src, 1);
+
+        // "a" can't be reached while the error buffer is full
+        let waker = futures::task::noop_waker();
+        let mut cx = std::task::Context::from_waker(&waker);
+        assert!(oks.poll_next_unpin(&mut cx).is_pending());
+
+        assert_eq!(block_on(errs.next()), Some(1));
+        assert_eq!(block_on(errs.next()), Some(2));
+        assert_eq!(block_on(oks.next()), Some("a"));
+        assert_eq!(block_on(errs.next()), Some(3));
+        assert_eq!(block_on(oks.next()), None);
+        assert_eq!(block_on(errs.next()), None);
+    }
+
+    #[test]
+    fn test_split_results_dropped_side() {
+        let src = futures::stream::iter(vec![Err(1), Ok("a"), Err(2), Ok("b")]);
+        let (oks, errs) = split_results(src, 1);
+        drop(errs);
+        assert_eq!(block_on(oks.collect::<Vec<_>>()), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_partition_results_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(1), Ok("b")]);
+        let mut raised = PartitionResults::new(src);
+        assert_eq!(run_future(&mut raised), ((vec!["a", "b"], vec![1]), 4));
+    }
+
+    #[test]
+    fn test_split_results_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(1), Ok("b"), Err(2)]);
+        let handle = src.handle();
+        let (oks, errs) = split_results(src, 1);
+        let mut both = futures::future::join(oks.collect::<Vec<_>>(), errs.collect::<Vec<_>>());
+        assert_eq!(run_future(&mut both).0, (vec!["a", "b"], vec![1, 2]));
+        assert_eq!(handle.polls_after_end(), 0);
+
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err(1), Ok("b"), Err(2)]);
+        let (oks, mut errs) = split_results(src, 1);
+        drop(oks);
+        assert_stream_yields(&mut errs, vec![1, 2]);
+    }
+}
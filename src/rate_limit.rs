@@ -0,0 +1,240 @@
+use crate::op_prelude::*;
+use crate::time::{deadline_after, Timer};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+pin_project! {
+    /// Stream for the [`rate_limit`](super::JStreamExt::rate_limit) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct RateLimit<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        bucket: TokenBucket,
+        #[pin]
+        delay: Option<T::Delay>,
+        held: Option<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for RateLimit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            if this.held.is_none() {
+                if *this.src_done {
+                    break None;
+                }
+
+                match ready!(this.src.as_mut().poll_next(cx)) {
+                    Some(next) => *this.held = Some(next),
+                    None => {
+                        *this.src_done = true;
+                        break None;
+                    }
+                }
+            }
+
+            if let Some(next_token) = this.bucket.take(this.timer.now()) {
+                if this.delay.is_none() {
+                    this.delay.set(Some(this.timer.delay_until(next_token)));
+                }
+
+                ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+                this.delay.set(None);
+            } else {
+                break this.held.take();
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let held = if self.held.is_some() { 1 } else { 0 };
+        if self.src_done {
+            (held, Some(held))
+        } else {
+            let (lower, upper) = self.src.size_hint();
+            (
+                lower.saturating_add(held),
+                upper.and_then(|upper| upper.checked_add(held)),
+            )
+        }
+    }
+}
+
+impl<S, T> FusedStream for RateLimit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.held.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for RateLimit<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> RateLimit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, n: usize, per: Duration, timer: T) -> Self {
+        let n = n.max(1);
+        Self {
+            src,
+            timer,
+            bucket: TokenBucket::new(per / u32::try_from(n).unwrap_or(u32::MAX), n),
+            delay: None,
+            held: None,
+            src_done: false,
+        }
+    }
+
+    ///
+    /// Set the number of items which can be emitted back-to-back after the stream has been idle
+    /// (the size of the token bucket). The default is `n`, and a value of `0` is treated as `1`.
+    ///
+    /// The bucket starts full, so up to `burst` items are emitted right away. This should be
+    /// called before the stream is polled.
+    ///
+    pub fn burst(mut self, burst: usize) -> Self {
+        self.bucket = TokenBucket::new(self.bucket.interval, burst.max(1));
+        self
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> RateLimit<S, T2> {
+        RateLimit {
+            src: self.src,
+            timer,
+            bucket: self.bucket,
+            delay: None,
+            held: None,
+            src_done: false,
+        }
+    }
+}
+
+struct TokenBucket {
+    interval: Duration,
+    capacity: usize,
+    tokens: usize,
+    last_refill: Option<Instant>,
+}
+
+impl TokenBucket {
+    fn new(interval: Duration, capacity: usize) -> Self {
+        Self {
+            interval,
+            capacity,
+            tokens: capacity,
+            last_refill: None,
+        }
+    }
+
+    // takes a token if one is available, otherwise returns the time at which the next one will be
+    fn take(&mut self, now: Instant) -> Option<Instant> {
+        self.refill(now);
+        if self.tokens > 0 {
+            self.tokens -= 1;
+            None
+        } else {
+            Some(deadline_after(self.last_refill.expect("set by refill"), self.interval))
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let last_refill = self.last_refill.get_or_insert(now);
+        if self.tokens >= self.capacity || self.interval == Duration::from_secs(0) {
+            // a full bucket doesn't accumulate time towards the next token
+            self.tokens = self.capacity;
+            *last_refill = now;
+            return;
+        }
+
+        let elapsed = now.saturating_duration_since(*last_refill);
+        let added = elapsed.as_nanos() / self.interval.as_nanos();
+        if added == 0 {
+            return;
+        }
+
+        let added = usize::try_from(added).unwrap_or(usize::MAX);
+        self.tokens = self.tokens.saturating_add(added).min(self.capacity);
+        if self.tokens == self.capacity {
+            *last_refill = now;
+        } else {
+            // fewer than `capacity` tokens were added, so this fits in a u32 whenever capacity does
+            *last_refill += self.interval * u32::try_from(added).unwrap_or(u32::MAX);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimit;
+    use crate::test_util::TimedHarness;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rate_limit() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::iter(1..=5);
+        let mut limited = RateLimit::new(src, 2, Duration::from_secs(1), harness.clock());
+
+        // the bucket starts full
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(2)));
+        assert_eq!(harness.poll(&mut limited), Poll::Pending);
+        assert_eq!(harness.clock().pending_delays(), 1);
+
+        // one token every 500ms after that
+        harness.advance(Duration::from_millis(499));
+        assert_eq!(harness.poll(&mut limited), Poll::Pending);
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut limited), Poll::Pending);
+
+        // idle time refills the bucket
+        harness.advance(Duration::from_secs(10));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(4)));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(5)));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_rate_limit_burst() {
+        let harness: TimedHarness = TimedHarness::new();
+        let src = futures::stream::iter(1..=3);
+        let mut limited = RateLimit::new(src, 10, Duration::from_secs(1), harness.clock()).burst(1);
+
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut limited), Poll::Pending);
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.wakes(), 1);
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(2)));
+        assert_eq!(harness.poll(&mut limited), Poll::Pending);
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut limited), Poll::Ready(None));
+    }
+}
@@ -0,0 +1,555 @@
+use crate::op_prelude::*;
+use crate::time::{DefaultTimer, Timer};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+///
+/// Something which can (re-)create the upstream of a [`RetryStream`].
+///
+/// You should not usually have to implement this yourself. Use [`retry_stream`](crate::retry_stream)
+/// or [`retry_stream_resumable`](crate::retry_stream_resumable) instead, which wrap a closure in
+/// [`FreshFactory`] or [`ResumableFactory`] respectively.
+///
+pub trait StreamFactory {
+    /// The type of stream created by this factory
+    type Stream: TryStream;
+
+    /// Some value which identifies how far into the upstream we have consumed
+    type Token;
+
+    ///
+    /// Create a new upstream. The `resume` token is the last token returned by
+    /// [`checkpoint`](StreamFactory::checkpoint), or `None` if nothing has been consumed yet.
+    ///
+    fn create(&mut self, resume: Option<&Self::Token>) -> Self::Stream;
+
+    ///
+    /// Called with every `Ok` item emitted by the upstream. If this returns `Some(token)`, then
+    /// that token will be passed to the next call to [`create`](StreamFactory::create).
+    ///
+    fn checkpoint(&mut self, item: &<Self::Stream as TryStream>::Ok) -> Option<Self::Token>;
+}
+
+/// Factory for the [`retry_stream`](crate::retry_stream) function, which always starts over
+pub struct FreshFactory<F> {
+    factory: F,
+}
+
+impl<F, S> StreamFactory for FreshFactory<F>
+where
+    F: FnMut() -> S,
+    S: TryStream,
+{
+    type Stream = S;
+    type Token = ();
+
+    fn create(&mut self, _: Option<&()>) -> S {
+        (self.factory)()
+    }
+
+    fn checkpoint(&mut self, _: &S::Ok) -> Option<()> {
+        None
+    }
+}
+
+/// Factory for the [`retry_stream_resumable`](crate::retry_stream_resumable) function
+pub struct ResumableFactory<F, C, K> {
+    factory: F,
+    checkpoint: C,
+    _token: PhantomData<fn() -> K>,
+}
+
+impl<F, C, S, K> StreamFactory for ResumableFactory<F, C, K>
+where
+    F: FnMut(Option<&K>) -> S,
+    C: FnMut(&S::Ok) -> Option<K>,
+    S: TryStream,
+{
+    type Stream = S;
+    type Token = K;
+
+    fn create(&mut self, resume: Option<&K>) -> S {
+        (self.factory)(resume)
+    }
+
+    fn checkpoint(&mut self, item: &S::Ok) -> Option<K> {
+        (self.checkpoint)(item)
+    }
+}
+
+///
+/// Decides how long a [`RetryStream`] waits before re-creating its upstream after an error, and
+/// when it should give up.
+///
+pub trait Backoff {
+    /// Future which completes once the delay has elapsed
+    type Delay: Future<Output = ()>;
+
+    ///
+    /// Called with the number of consecutive failures seen so far (starting at `1`). Returning
+    /// `None` gives up, which causes the error to be emitted and the stream to end.
+    ///
+    fn next_delay(&mut self, attempt: u32) -> Option<Self::Delay>;
+}
+
+///
+/// How an [`ExponentialBackoff`] waits between attempts.
+///
+/// This is implemented for [`TimerSleep`], which waits using any [`Timer`](crate::time::Timer),
+/// and for any `FnMut(Duration) -> Future<Output = ()>` function (such as `tokio::time::sleep`).
+///
+pub trait Sleep {
+    /// Future which completes once the duration has elapsed
+    type Delay: Future<Output = ()>;
+
+    /// Create a future which completes once `duration` has elapsed
+    fn sleep(&mut self, duration: Duration) -> Self::Delay;
+}
+
+impl<F, Fut> Sleep for F
+where
+    F: FnMut(Duration) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    type Delay = Fut;
+
+    fn sleep(&mut self, duration: Duration) -> Fut {
+        self(duration)
+    }
+}
+
+/// The [`Sleep`] used by [`ExponentialBackoff::with_timer`]
+#[derive(Debug, Clone, Copy)]
+pub struct TimerSleep<T>(T);
+
+impl<T> Sleep for TimerSleep<T>
+where
+    T: Timer,
+{
+    type Delay = T::Delay;
+
+    fn sleep(&mut self, duration: Duration) -> T::Delay {
+        self.0.delay(duration)
+    }
+}
+
+///
+/// A [`Backoff`] which waits exponentially longer after each consecutive failure.
+///
+/// [`with_default_timer`](ExponentialBackoff::with_default_timer) waits using the
+/// [`DefaultTimer`](crate::time::DefaultTimer), which can be replaced with
+/// [`with_timer`](ExponentialBackoff::with_timer), see [Time](crate#time). Alternatively,
+/// [`new`](ExponentialBackoff::new) accepts any `sleep` function which turns a
+/// [`Duration`](std::time::Duration) into a `Future` (such as `tokio::time::sleep`).
+///
+/// By default the delay starts at `initial`, doubles after every failure, is capped at 30
+/// seconds, has no jitter, and there is no limit on the number of attempts.
+///
+pub struct ExponentialBackoff<F> {
+    initial: Duration,
+    max_delay: Duration,
+    factor: u32,
+    jitter: bool,
+    max_attempts: Option<u32>,
+    hasher: RandomState,
+    sleep: F,
+}
+
+impl ExponentialBackoff<TimerSleep<DefaultTimer>> {
+    /// Create a new backoff which waits `initial` after the first failure, using the default timer
+    pub fn with_default_timer(initial: Duration) -> Self {
+        Self::from_sleep(initial, TimerSleep(DefaultTimer::default()))
+    }
+}
+
+impl<F, Fut> ExponentialBackoff<F>
+where
+    F: FnMut(Duration) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    /// Create a new backoff which waits `initial` after the first failure, using `sleep` to wait
+    pub fn new(initial: Duration, sleep: F) -> Self {
+        Self::from_sleep(initial, sleep)
+    }
+}
+
+impl<F> ExponentialBackoff<F>
+where
+    F: Sleep,
+{
+    fn from_sleep(initial: Duration, sleep: F) -> Self {
+        Self {
+            initial,
+            max_delay: Duration::from_secs(30),
+            factor: 2,
+            jitter: false,
+            max_attempts: None,
+            hasher: RandomState::default(),
+            sleep,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) (or `sleep` function) used by this backoff, see
+    /// [Time](crate#time)
+    pub fn with_timer<T: Timer>(self, timer: T) -> ExponentialBackoff<TimerSleep<T>> {
+        ExponentialBackoff {
+            initial: self.initial,
+            max_delay: self.max_delay,
+            factor: self.factor,
+            jitter: self.jitter,
+            max_attempts: self.max_attempts,
+            hasher: self.hasher,
+            sleep: TimerSleep(timer),
+        }
+    }
+
+    /// Never wait longer than `max_delay` between attempts
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Multiply the delay by `factor` after every consecutive failure
+    pub fn factor(mut self, factor: u32) -> Self {
+        self.factor = factor;
+        self
+    }
+
+    ///
+    /// If enabled, the actual delay is chosen uniformly between zero and the computed delay, so
+    /// that many clients failing at once don't all retry at the same moment.
+    ///
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Give up after `max_attempts` consecutive failures
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    fn compute_delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .factor
+            .checked_pow(attempt.saturating_sub(1))
+            .and_then(|mul| self.initial.checked_mul(mul))
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+
+        if self.jitter {
+            let nanos = delay.as_nanos().min(u64::MAX as u128) as u64;
+            let random = self.hasher.hash_one(attempt);
+            Duration::from_nanos(random % nanos.saturating_add(1))
+        } else {
+            delay
+        }
+    }
+}
+
+impl<F> Backoff for ExponentialBackoff<F>
+where
+    F: Sleep,
+{
+    type Delay = F::Delay;
+
+    fn next_delay(&mut self, attempt: u32) -> Option<F::Delay> {
+        if let Some(max) = self.max_attempts {
+            if attempt > max {
+                return None;
+            }
+        }
+
+        let delay = self.compute_delay(attempt);
+        Some(self.sleep.sleep(delay))
+    }
+}
+
+pin_project! {
+    /// Stream for the [`retry_stream`](crate::retry_stream) and
+    /// [`retry_stream_resumable`](crate::retry_stream_resumable) functions
+    #[must_use = "streams do nothing unless polled"]
+    pub struct RetryStream<F, B>
+    where
+        F: StreamFactory,
+        B: Backoff,
+    {
+        factory: F,
+        backoff: B,
+        #[pin]
+        current: Option<F::Stream>,
+        #[pin]
+        delay: Option<B::Delay>,
+        token: Option<F::Token>,
+        attempt: u32,
+        done: bool,
+    }
+}
+
+impl<F, B> Stream for RetryStream<F, B>
+where
+    F: StreamFactory,
+    B: Backoff,
+{
+    type Item = Result<<F::Stream as TryStream>::Ok, <F::Stream as TryStream>::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        Poll::Ready(loop {
+            // wait out the backoff if we're between attempts
+            if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+                ready!(delay.poll(cx));
+                this.delay.set(None);
+            }
+
+            if this.current.is_none() {
+                let next = this.factory.create(this.token.as_ref());
+                this.current.set(Some(next));
+            }
+
+            let current = this.current.as_mut().as_pin_mut().expect("upstream was just created");
+            match ready!(current.try_poll_next(cx)) {
+                Some(Ok(item)) => {
+                    *this.attempt = 0;
+                    if let Some(token) = this.factory.checkpoint(&item) {
+                        *this.token = Some(token);
+                    }
+                    break Some(Ok(item));
+                }
+                Some(Err(err)) => {
+                    this.current.set(None);
+                    *this.attempt = this.attempt.saturating_add(1);
+                    match this.backoff.next_delay(*this.attempt) {
+                        Some(delay) => this.delay.set(Some(delay)),
+                        None => {
+                            *this.done = true;
+                            break Some(Err(err));
+                        }
+                    }
+                }
+                None => {
+                    this.current.set(None);
+                    *this.done = true;
+                    break None;
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            (0, Some(0))
+        } else {
+            (0, None)
+        }
+    }
+}
+
+impl<F, B> FusedStream for RetryStream<F, B>
+where
+    F: StreamFactory,
+    B: Backoff,
+{
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<F, B> RetryStream<F, B>
+where
+    F: StreamFactory,
+    B: Backoff,
+{
+    pub(crate) fn new(factory: F, backoff: B) -> Self {
+        Self {
+            factory,
+            backoff,
+            current: None,
+            delay: None,
+            token: None,
+            attempt: 0,
+            done: false,
+        }
+    }
+}
+
+///
+/// Create a [`TryStream`](futures::TryStream) which calls `factory` to create an upstream, and
+/// calls it again to create a fresh upstream whenever the current one emits an error.
+///
+/// Before every re-creation, the `backoff` is asked how long to wait. Once the backoff gives up,
+/// the last error is emitted and the stream ends. The attempt counter resets whenever the
+/// upstream emits an `Ok` item. Errors which cause a retry are not emitted.
+///
+/// If the upstream completes without error, the stream completes.
+///
+pub fn retry_stream<F, S, B>(factory: F, backoff: B) -> RetryStream<FreshFactory<F>, B>
+where
+    F: FnMut() -> S,
+    S: TryStream,
+    B: Backoff,
+{
+    RetryStream::new(FreshFactory { factory }, backoff)
+}
+
+///
+/// Same as [`retry_stream`](crate::retry_stream), but the `factory` is given a resume token so
+/// that consumption can continue where it stopped.
+///
+/// Every `Ok` item emitted by the upstream is passed to `checkpoint`, and the most recent `Some`
+/// token it returned is passed to `factory` when the upstream is re-created. The first call to
+/// `factory` is given `None`.
+///
+pub fn retry_stream_resumable<F, C, S, K, B>(
+    factory: F,
+    checkpoint: C,
+    backoff: B,
+) -> RetryStream<ResumableFactory<F, C, K>, B>
+where
+    F: FnMut(Option<&K>) -> S,
+    C: FnMut(&S::Ok) -> Option<K>,
+    S: TryStream,
+    B: Backoff,
+{
+    let factory = ResumableFactory {
+        factory,
+        checkpoint,
+        _token: PhantomData,
+    };
+    RetryStream::new(factory, backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{retry_stream, retry_stream_resumable, Backoff, ExponentialBackoff};
+    use crate::test_util::{assert_stream_yields, CountingWaker, ScriptedStream};
+    use crate::time::VirtualClock;
+    use futures::executor::block_on;
+    use futures::future::{ready, Ready};
+    use futures::{FutureExt, TryStreamExt};
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    fn no_sleep(_: Duration) -> Ready<()> {
+        ready(())
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let mut calls = 0;
+        let stream = retry_stream(
+            || {
+                calls += 1;
+                let items: Vec<Result<u32, &str>> = if calls < 3 {
+                    vec![Ok(1), Err("oh no!")]
+                } else {
+                    vec![Ok(1), Ok(2)]
+                };
+                futures::stream::iter(items)
+            },
+            ExponentialBackoff::new(Duration::from_millis(1), no_sleep),
+        );
+
+        assert_eq!(block_on(stream.try_collect::<Vec<_>>()), Ok(vec![1, 1, 1, 2]));
+    }
+
+    #[test]
+    fn test_retry_gives_up() {
+        let mut calls = 0;
+        let mut stream = retry_stream(
+            || {
+                calls += 1;
+                let items = if calls == 1 { vec![Ok(1), Err("oh no!")] } else { vec![Err("oh no!")] };
+                futures::stream::iter(items)
+            },
+            ExponentialBackoff::new(Duration::from_millis(1), no_sleep).max_attempts(2),
+        );
+
+        assert_eq!(block_on(stream.try_next()), Ok(Some(1)));
+        assert_eq!(block_on(stream.try_next()), Err("oh no!"));
+        assert_eq!(block_on(stream.try_next()), Ok(None));
+    }
+
+    #[test]
+    fn test_retry_resumes() {
+        let stream = retry_stream_resumable(
+            |resume: Option<&u32>| {
+                let start = resume.map_or(0, |last| last + 1);
+                let items: Vec<Result<u32, &str>> = (start..5)
+                    .map(|n| if n == 2 && start == 0 { Err("oh no!") } else { Ok(n) })
+                    .collect();
+                futures::stream::iter(items)
+            },
+            |item: &u32| Some(*item),
+            ExponentialBackoff::new(Duration::from_millis(1), no_sleep),
+        );
+
+        assert_eq!(block_on(stream.try_collect::<Vec<_>>()), Ok(vec![0, 1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_retry_pending() {
+        let mut calls = 0;
+        let mut stream = retry_stream(
+            || {
+                calls += 1;
+                let items = if calls == 1 { vec![Ok(1), Err("oh no!")] } else { vec![Ok(2)] };
+                ScriptedStream::interleaved(items)
+            },
+            ExponentialBackoff::new(Duration::from_millis(1), no_sleep),
+        );
+
+        assert_stream_yields(&mut stream, vec![Ok(1), Ok(2)]);
+        assert!(futures::stream::FusedStream::is_terminated(&stream));
+    }
+
+    #[test]
+    fn test_exponential_delays() {
+        let mut delays = Vec::new();
+        {
+            let mut backoff = ExponentialBackoff::new(Duration::from_millis(100), |d| {
+                delays.push(d);
+                ready(())
+            })
+            .max_delay(Duration::from_millis(500));
+
+            for attempt in 1..=5 {
+                assert!(super::Backoff::next_delay(&mut backoff, attempt).is_some());
+            }
+        }
+
+        let millis: Vec<u128> = delays.iter().map(Duration::as_millis).collect();
+        assert_eq!(millis, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn test_jitter_bounded() {
+        let backoff = ExponentialBackoff::new(Duration::from_millis(100), no_sleep).jitter(true);
+        for attempt in 1..=4 {
+            assert!(backoff.compute_delay(attempt) <= Duration::from_millis(100 << (attempt - 1)));
+        }
+    }
+
+    #[test]
+    fn test_backoff_with_timer() {
+        let clock = VirtualClock::new();
+        let waker = CountingWaker::new();
+        let task_waker = waker.waker();
+        let mut cx = Context::from_waker(&task_waker);
+
+        let mut backoff = ExponentialBackoff::with_default_timer(Duration::from_millis(100))
+            .max_attempts(1)
+            .with_timer(clock.clone());
+        let mut delay = backoff.next_delay(1).expect("first attempt");
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(waker.count(), 1);
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Ready(()));
+        assert!(backoff.next_delay(2).is_none());
+    }
+}
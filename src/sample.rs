@@ -0,0 +1,340 @@
+use crate::op_prelude::*;
+use crate::time::{deadline_after, skip_to, yield_now, Timer, POLL_BUDGET};
+use std::time::{Duration, Instant};
+
+pin_project! {
+    /// Stream for the [`sample`](super::JStreamExt::sample) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Sample<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        period: Duration,
+        next_tick: Option<Instant>,
+        #[pin]
+        delay: Option<T::Delay>,
+        latest: Option<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for Sample<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let mut taken = 0;
+        loop {
+            let now = this.timer.now();
+            let tick = *this.next_tick.get_or_insert(deadline_after(now, *this.period));
+            if now >= tick {
+                *this.next_tick = Some(skip_to(tick, now, *this.period));
+                this.delay.set(None);
+                if this.latest.is_some() {
+                    return Poll::Ready(this.latest.take());
+                }
+            }
+
+            if *this.src_done {
+                return Poll::Ready(this.latest.take());
+            }
+
+            if taken == POLL_BUDGET {
+                return yield_now(cx);
+            }
+
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    *this.latest = Some(next);
+                    taken += 1;
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                }
+                Poll::Pending => {
+                    // periods without any items are skipped, so the timer is only needed once one arrives
+                    if this.latest.is_none() {
+                        return Poll::Pending;
+                    }
+
+                    if this.delay.is_none() {
+                        this.delay.set(Some(this.timer.delay_until(tick)));
+                    }
+                    ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        latest_size_hint(self.src_done, self.latest.is_some(), self.src.size_hint())
+    }
+}
+
+impl<S, T> FusedStream for Sample<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.latest.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for Sample<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> Sample<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, period: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            period: period.max(Duration::from_nanos(1)),
+            next_tick: None,
+            delay: None,
+            latest: None,
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> Sample<S, T2> {
+        Sample::new(self.src, self.period, timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`audit`](super::JStreamExt::audit) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Audit<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        duration: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        latest: Option<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for Audit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let mut taken = 0;
+        Poll::Ready(loop {
+            if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+                if delay.poll(cx).is_ready() {
+                    this.delay.set(None);
+                    break this.latest.take();
+                }
+            }
+
+            if *this.src_done {
+                break this.latest.take();
+            }
+
+            if taken == POLL_BUDGET {
+                return yield_now(cx);
+            }
+
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    taken += 1;
+                    // unlike debounce, new items don't restart the timer
+                    if this.delay.is_none() {
+                        this.delay.set(Some(this.timer.delay(*this.duration)));
+                    }
+                    *this.latest = Some(next);
+                }
+                None => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                }
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        latest_size_hint(self.src_done, self.latest.is_some(), self.src.size_hint())
+    }
+}
+
+impl<S, T> FusedStream for Audit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.latest.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for Audit<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> Audit<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            duration,
+            delay: None,
+            latest: None,
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> Audit<S, T2> {
+        Audit::new(self.src, self.duration, timer)
+    }
+}
+
+fn latest_size_hint(
+    src_done: bool,
+    has_latest: bool,
+    (src_lower, src_upper): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let latest = if has_latest { 1 } else { 0 };
+    if src_done {
+        (latest, Some(latest))
+    } else {
+        // any number of items can be collapsed into one, but at least one will come out
+        (
+            src_lower.saturating_add(latest).min(1),
+            src_upper.and_then(|upper| upper.checked_add(latest)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Audit, Sample};
+    use crate::test_util::TimedHarness;
+    use futures::stream;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sample() {
+        let mut harness = TimedHarness::new();
+        let mut sampled = Sample::new(harness.source(), Duration::from_secs(1), harness.clock());
+
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.send(1);
+        harness.send(2);
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.advance(Duration::from_millis(600));
+        harness.send(3);
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.advance(Duration::from_millis(400));
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(Some(3)));
+
+        // nothing is emitted for a period without items
+        harness.advance(Duration::from_millis(1500));
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.send(4);
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.advance(Duration::from_millis(499));
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(Some(4)));
+
+        harness.send(5);
+        harness.close();
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(Some(5)));
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_audit() {
+        let mut harness = TimedHarness::new();
+        let mut audited = Audit::new(harness.source(), Duration::from_secs(1), harness.clock());
+
+        assert_eq!(harness.poll(&mut audited), Poll::Pending);
+        assert_eq!(harness.clock().pending_delays(), 0);
+
+        // the first item starts the timer, and later items don't restart it
+        harness.send(1);
+        assert_eq!(harness.poll(&mut audited), Poll::Pending);
+        harness.advance(Duration::from_millis(900));
+        harness.send(2);
+        assert_eq!(harness.poll(&mut audited), Poll::Pending);
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.poll(&mut audited), Poll::Ready(Some(2)));
+        assert_eq!(harness.poll(&mut audited), Poll::Pending);
+        assert_eq!(harness.clock().pending_delays(), 0);
+
+        harness.send(3);
+        harness.close();
+        assert_eq!(harness.poll(&mut audited), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut audited), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_sample_always_ready_source() {
+        // a source which never goes quiet still lets each poll return
+        let harness: TimedHarness = TimedHarness::new();
+        let second = Duration::from_secs(1);
+        let mut sampled = Sample::new(stream::repeat(1), second, harness.clock());
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+
+        let harness: TimedHarness = TimedHarness::new();
+        let mut audited = Audit::new(stream::repeat(1), second, harness.clock());
+        assert_eq!(harness.poll(&mut audited), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+    }
+
+    #[test]
+    fn test_sample_long_period() {
+        let mut harness = TimedHarness::new();
+        let mut sampled = Sample::new(harness.source(), Duration::MAX, harness.clock());
+
+        harness.send(1);
+        assert_eq!(harness.poll(&mut sampled), Poll::Pending);
+        harness.close();
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut sampled), Poll::Ready(None));
+    }
+}
@@ -0,0 +1,151 @@
+use crate::op_prelude::*;
+use futures::channel::oneshot;
+
+pin_project! {
+    /// Stream half of the [`split_error`](super::JTryStreamExt::split_error) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct SplitErrorStream<S>
+    where
+        S: TryStream,
+    {
+        #[pin]
+        src: S,
+        tx: Option<oneshot::Sender<S::Error>>,
+    }
+}
+
+impl<S> Stream for SplitErrorStream<S>
+where
+    S: TryStream,
+{
+    type Item = S::Ok;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        if this.tx.is_none() {
+            return Poll::Ready(None);
+        }
+
+        Poll::Ready(match ready!(this.src.try_poll_next(cx)) {
+            Some(Ok(next)) => Some(next),
+            Some(Err(err)) => {
+                // the future may have been dropped, in which case nobody cares about the error
+                let _ = this.tx.take().expect("checked above").send(err);
+                None
+            }
+            None => {
+                this.tx.take();
+                None
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.tx.is_none() {
+            (0, Some(0))
+        } else {
+            (0, self.src.size_hint().1)
+        }
+    }
+}
+
+impl<S> FusedStream for SplitErrorStream<S>
+where
+    S: TryStream,
+{
+    fn is_terminated(&self) -> bool {
+        self.tx.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, Item, E> Sink<Item> for SplitErrorStream<S>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+///
+/// Future half of the [`split_error`](super::JTryStreamExt::split_error) method
+///
+/// Resolves to `Some(error)` if the stream half ended because of an error, or `None` if the stream
+/// half completed normally (or was dropped before it completed).
+///
+#[must_use = "futures do nothing unless polled"]
+pub struct SplitErrorFuture<E> {
+    rx: oneshot::Receiver<E>,
+}
+
+impl<E> Future for SplitErrorFuture<E> {
+    type Output = Option<E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Poll::Ready(ready!(Pin::new(&mut self.rx).poll(cx)).ok())
+    }
+}
+
+pub(crate) fn split_error<S>(src: S) -> (SplitErrorStream<S>, SplitErrorFuture<S::Error>)
+where
+    S: TryStream,
+{
+    let (tx, rx) = oneshot::channel();
+    (SplitErrorStream { src, tx: Some(tx) }, SplitErrorFuture { rx })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_error;
+    use crate::test_util::{assert_stream_yields, run_future, ScriptedStream};
+    use futures::executor::block_on;
+    use futures::{Stream, StreamExt};
+
+    #[test]
+    fn test_split_error() {
+        let src = futures::stream::iter(vec![Ok("a"), Ok("b"), Err("oh no!"), Ok("c")]);
+        let (values, error) = split_error(src);
+
+        assert_eq!(block_on(values.collect::<Vec<_>>()), vec!["a", "b"]);
+        assert_eq!(block_on(error), Some("oh no!"));
+    }
+
+    #[test]
+    fn test_split_error_none() {
+        let items: Vec<Result<&str, ()>> = vec![Ok("a"), Ok("b")];
+        let (values, error) = split_error(futures::stream::iter(items));
+
+        assert_eq!(block_on(values.collect::<Vec<_>>()), vec!["a", "b"]);
+        assert_eq!(block_on(error), None);
+    }
+
+    #[test]
+    fn test_split_error_dropped() {
+        let items: Vec<Result<&str, ()>> = vec![Ok("a"), Err(())];
+        let (values, error) = split_error(futures::stream::iter(items));
+
+        drop(values);
+        assert_eq!(block_on(error), None);
+    }
+
+    #[test]
+    fn test_split_error_size_hint() {
+        let src = futures::stream::iter(vec![Ok("a"), Err("oh no!"), Ok("b")]);
+        let (mut values, _error) = split_error(src);
+        assert_eq!(values.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(values.next()), Some("a"));
+        assert_eq!(values.size_hint(), (0, Some(2)));
+        assert_eq!(block_on(values.next()), None);
+        assert_eq!(values.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_split_error_pending() {
+        let src = ScriptedStream::interleaved(vec![Ok("a"), Err("oh no!"), Ok("b")]);
+        let handle = src.handle();
+        let (mut values, mut error) = split_error(src);
+
+        assert_stream_yields(&mut values, vec!["a"]);
+        assert_eq!(run_future(&mut error), (Some("oh no!"), 0));
+        assert!(!handle.is_finished());
+    }
+}
@@ -0,0 +1,675 @@
+//!
+//! Helpers for testing [`Stream`](futures::Stream) and [`Future`](futures::Future) implementations.
+//!
+//! Driving a stream made with [`futures::stream::iter`] using `block_on` never exercises
+//! `Poll::Pending`, waker propagation, or polling after completion. The helpers in this module do:
+//!
+//! * [`ScriptedStream`] emits a scripted sequence of items and `Poll::Pending` results, and records
+//!   how it was polled (see [`ScriptHandle`]).
+//! * [`CountingWaker`] is a [`Waker`](std::task::Waker) which counts how many times it was woken.
+//! * [`poll_to_end`] and [`run_future`] drive a stream or future to completion, and panic if it ever
+//!   returns `Poll::Pending` without arranging for the task to be woken.
+//! * [`assert_stream_yields`] checks the items emitted by a stream.
+//! * [`TimedHarness`] bundles a [`VirtualClock`](crate::time::VirtualClock), a [`CountingWaker`]
+//!   and a source stream which items are pushed into by hand, for testing timed streams.
+//!
+//! # Marble Diagrams
+//!
+//! For order-sensitive combinators, it's often easier to describe streams using a small "marble
+//! diagram" string, where every character is a single poll result:
+//!
+//! * `-` is a `Poll::Pending` gap (which wakes the task immediately)
+//! * `#` is an error (`Err(MarbleError)`)
+//! * `|` is the completion of the stream (`Poll::Ready(None)`)
+//! * whitespace is ignored
+//! * any other character is an item
+//!
+//! If a diagram does not end with `|`, the stream never completes (it returns `Poll::Pending`
+//! forever without waking the task).
+//!
+//! Use [`marbles`] (or [`marbles_with`] and [`marble_items`]) to build a [`ScriptedStream`] from a
+//! diagram, and [`assert_marbles`] to check the output of a stream against a diagram:
+//!
+//! ```
+//! use jstream_ext::JTryStreamExt;
+//! use jstream_ext::test_util::{assert_marbles, marbles};
+//!
+//! let mut stream = marbles("a-b-#-c|").fuse_on_fail();
+//! assert_marbles(&mut stream, "a-b-#|");
+//! ```
+//!
+//! This module is only available when the `test-util` feature is enabled.
+//!
+
+use crate::time::VirtualClock;
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use futures::stream::FusedStream;
+use futures::task::{waker, ArcWake};
+use futures::{Future, Stream};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// How many `Poll::Pending` results in a row are tolerated before assuming something is stuck
+const MAX_CONSECUTIVE_PENDING: usize = 10_000;
+
+///
+/// A single step of a [`ScriptedStream`], or a single poll result recorded by [`poll_to_end`].
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step<T> {
+    /// `Poll::Ready(Some(T))`
+    Item(T),
+    /// `Poll::Pending`
+    Pending,
+}
+
+///
+/// A [`Stream`](futures::Stream) which emits a scripted sequence of items and `Poll::Pending`
+/// results, then completes.
+///
+/// Every [`Step::Pending`] immediately wakes the task it was polled from before returning
+/// `Poll::Pending`, so adapters which pass the waker through correctly will be polled again.
+///
+/// Polling this stream after it completes is allowed (it emits `None` again), but is recorded, and
+/// can be checked using [`ScriptHandle::polls_after_end`].
+///
+pub struct ScriptedStream<T> {
+    steps: VecDeque<Step<T>>,
+    completes: bool,
+    state: Arc<ScriptState>,
+}
+
+#[derive(Default)]
+struct ScriptState {
+    polls: AtomicUsize,
+    polls_after_end: AtomicUsize,
+    ended: AtomicBool,
+}
+
+///
+/// Handle which can be used to inspect how a [`ScriptedStream`] was polled, even after it has been
+/// moved into some adapter.
+///
+#[derive(Clone)]
+pub struct ScriptHandle {
+    state: Arc<ScriptState>,
+}
+
+impl<T> ScriptedStream<T> {
+    /// Create a stream which follows the given script
+    pub fn new<I>(steps: I) -> Self
+    where
+        I: IntoIterator<Item = Step<T>>,
+    {
+        Self {
+            steps: steps.into_iter().collect(),
+            completes: true,
+            state: Arc::default(),
+        }
+    }
+
+    ///
+    /// Instead of completing after the last step, return `Poll::Pending` forever without waking
+    /// the task (like [`futures::stream::pending`]).
+    ///
+    pub fn without_completion(mut self) -> Self {
+        self.completes = false;
+        self
+    }
+
+    /// Create a stream which emits every item without ever returning `Poll::Pending`
+    pub fn items<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        Self::new(items.into_iter().map(Step::Item))
+    }
+
+    /// Create a stream which returns `Poll::Pending` once before every item, and before completing
+    pub fn interleaved<I>(items: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut steps: Vec<Step<T>> = items
+            .into_iter()
+            .flat_map(|item| vec![Step::Pending, Step::Item(item)])
+            .collect();
+        steps.push(Step::Pending);
+        Self::new(steps)
+    }
+
+    /// Get a handle which can be used to inspect how this stream was polled
+    pub fn handle(&self) -> ScriptHandle {
+        ScriptHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl ScriptHandle {
+    /// The number of times the stream was polled
+    pub fn polls(&self) -> usize {
+        self.state.polls.load(Ordering::SeqCst)
+    }
+
+    /// The number of times the stream was polled after it had already emitted `None`
+    pub fn polls_after_end(&self) -> usize {
+        self.state.polls_after_end.load(Ordering::SeqCst)
+    }
+
+    /// Whether the stream has emitted `None`
+    pub fn is_finished(&self) -> bool {
+        self.state.ended.load(Ordering::SeqCst)
+    }
+}
+
+impl<T> Unpin for ScriptedStream<T> {}
+
+impl<T> Stream for ScriptedStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.state.polls.fetch_add(1, Ordering::SeqCst);
+        match self.steps.pop_front() {
+            Some(Step::Item(item)) => Poll::Ready(Some(item)),
+            Some(Step::Pending) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            None if !self.completes => Poll::Pending,
+            None => {
+                if self.state.ended.swap(true, Ordering::SeqCst) {
+                    self.state.polls_after_end.fetch_add(1, Ordering::SeqCst);
+                }
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self
+            .steps
+            .iter()
+            .filter(|step| matches!(step, Step::Item(_)))
+            .count();
+        (remaining, if self.completes { Some(remaining) } else { None })
+    }
+}
+
+impl<T> FusedStream for ScriptedStream<T> {
+    fn is_terminated(&self) -> bool {
+        self.state.ended.load(Ordering::SeqCst)
+    }
+}
+
+///
+/// A [`Waker`](std::task::Waker) which counts how many times it has been woken.
+///
+#[derive(Clone, Default)]
+pub struct CountingWaker {
+    counter: Arc<WakeCounter>,
+}
+
+#[derive(Default)]
+struct WakeCounter {
+    count: AtomicUsize,
+}
+
+impl ArcWake for WakeCounter {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+impl CountingWaker {
+    /// Create a new waker which has not been woken
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a [`Waker`](std::task::Waker) which increments this counter when woken
+    pub fn waker(&self) -> Waker {
+        waker(self.counter.clone())
+    }
+
+    /// The number of times any waker created by [`waker`](CountingWaker::waker) was woken
+    pub fn count(&self) -> usize {
+        self.counter.count.load(Ordering::SeqCst)
+    }
+}
+
+///
+/// Poll the stream once using the given [`CountingWaker`].
+///
+pub fn poll_once<S>(stream: &mut S, waker: &CountingWaker) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let waker = waker.waker();
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+///
+/// Fixture for testing timed streams step by step.
+///
+/// Build the stream under test from [`source`](TimedHarness::source) (a stream which only emits
+/// what is given to [`send`](TimedHarness::send)) and [`clock`](TimedHarness::clock), then drive
+/// it with [`poll`](TimedHarness::poll) and [`advance`](TimedHarness::advance):
+///
+/// ```
+/// use jstream_ext::JStreamExt;
+/// use jstream_ext::test_util::TimedHarness;
+/// use std::task::Poll;
+/// use std::time::Duration;
+///
+/// let mut harness = TimedHarness::new();
+/// let mut delayed = harness.source().delay(Duration::from_secs(1)).with_timer(harness.clock());
+///
+/// harness.send("a");
+/// assert_eq!(harness.poll(&mut delayed), Poll::Pending);
+/// harness.advance(Duration::from_secs(1));
+/// assert_eq!(harness.poll(&mut delayed), Poll::Ready(Some("a")));
+/// ```
+///
+pub struct TimedHarness<T = ()> {
+    clock: VirtualClock,
+    waker: CountingWaker,
+    tx: Option<UnboundedSender<T>>,
+    rx: Option<UnboundedReceiver<T>>,
+}
+
+impl<T> TimedHarness<T> {
+    /// Create a new harness, with its clock stopped at the current time
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        Self {
+            clock: VirtualClock::new(),
+            waker: CountingWaker::new(),
+            tx: Some(tx),
+            rx: Some(rx),
+        }
+    }
+
+    ///
+    /// Take the source stream, which emits every item given to [`send`](TimedHarness::send), and
+    /// ends once [`close`](TimedHarness::close) is called.
+    ///
+    /// # Panics
+    ///
+    /// If the source was already taken.
+    ///
+    pub fn source(&mut self) -> UnboundedReceiver<T> {
+        self.rx.take().expect("source already taken")
+    }
+
+    /// A handle to the [`VirtualClock`](crate::time::VirtualClock) of this harness
+    pub fn clock(&self) -> VirtualClock {
+        self.clock.clone()
+    }
+
+    /// Move the clock forward by `duration`, waking any delays which are now complete
+    pub fn advance(&self, duration: Duration) {
+        self.clock.advance(duration);
+    }
+
+    ///
+    /// Push an item into the source stream.
+    ///
+    /// # Panics
+    ///
+    /// If [`close`](TimedHarness::close) was already called.
+    ///
+    pub fn send(&self, item: T) {
+        self.tx
+            .as_ref()
+            .expect("source already closed")
+            .unbounded_send(item)
+            .expect("source dropped");
+    }
+
+    /// End the source stream, once the items which were already sent are emitted
+    pub fn close(&mut self) {
+        self.tx.take();
+    }
+
+    /// Poll the stream once using the [`CountingWaker`] of this harness
+    pub fn poll<S>(&self, stream: &mut S) -> Poll<Option<S::Item>>
+    where
+        S: Stream + Unpin,
+    {
+        poll_once(stream, &self.waker)
+    }
+
+    /// The number of times the stream polled by [`poll`](TimedHarness::poll) woke its task
+    pub fn wakes(&self) -> usize {
+        self.waker.count()
+    }
+}
+
+impl<T> Default for TimedHarness<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+///
+/// Poll the stream until it completes, recording every result.
+///
+/// # Panics
+///
+/// If the stream returns `Poll::Pending` without waking the task first (which means a real
+/// executor would never poll it again), or if it returns `Poll::Pending` too many times in a row.
+///
+pub fn poll_to_end<S>(stream: &mut S) -> Vec<Step<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let waker = CountingWaker::new();
+    let mut out = Vec::new();
+    let mut consecutive_pending = 0;
+    loop {
+        let wakes_before = waker.count();
+        match poll_once(stream, &waker) {
+            Poll::Ready(Some(item)) => {
+                consecutive_pending = 0;
+                out.push(Step::Item(item));
+            }
+            Poll::Ready(None) => break out,
+            Poll::Pending => {
+                assert!(
+                    waker.count() > wakes_before,
+                    "stream returned Poll::Pending without waking the task"
+                );
+                consecutive_pending += 1;
+                assert!(
+                    consecutive_pending < MAX_CONSECUTIVE_PENDING,
+                    "stream returned Poll::Pending {} times in a row",
+                    MAX_CONSECUTIVE_PENDING
+                );
+                out.push(Step::Pending);
+            }
+        }
+    }
+}
+
+///
+/// Poll the future until it completes, giving the output and the number of times it returned
+/// `Poll::Pending`.
+///
+/// # Panics
+///
+/// Under the same conditions as [`poll_to_end`].
+///
+pub fn run_future<F>(future: &mut F) -> (F::Output, usize)
+where
+    F: Future + Unpin,
+{
+    let waker = CountingWaker::new();
+    let mut pending = 0;
+    loop {
+        let wakes_before = waker.count();
+        let task_waker = waker.waker();
+        let mut cx = Context::from_waker(&task_waker);
+        match Pin::new(&mut *future).poll(&mut cx) {
+            Poll::Ready(out) => break (out, pending),
+            Poll::Pending => {
+                assert!(
+                    waker.count() > wakes_before,
+                    "future returned Poll::Pending without waking the task"
+                );
+                pending += 1;
+                assert!(
+                    pending < MAX_CONSECUTIVE_PENDING,
+                    "future returned Poll::Pending {} times in a row",
+                    MAX_CONSECUTIVE_PENDING
+                );
+            }
+        }
+    }
+}
+
+///
+/// Drive the stream to completion using [`poll_to_end`], and assert that it emitted exactly the
+/// `expected` items (ignoring any `Poll::Pending` results).
+///
+pub fn assert_stream_yields<S>(stream: &mut S, expected: Vec<S::Item>)
+where
+    S: Stream + Unpin,
+    S::Item: Debug + PartialEq,
+{
+    let items: Vec<S::Item> = poll_to_end(stream)
+        .into_iter()
+        .filter_map(|step| match step {
+            Step::Item(item) => Some(item),
+            Step::Pending => None,
+        })
+        .collect();
+    assert_eq!(items, expected);
+}
+
+///
+/// The error emitted for `#` by the streams built from marble diagrams (see the
+/// [module documentation](self)).
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MarbleError;
+
+///
+/// Build a [`ScriptedStream`] from a marble diagram (see the [module documentation](self)), where
+/// every item is `Ok(char)` and `#` is `Err(MarbleError)`.
+///
+pub fn marbles(diagram: &str) -> ScriptedStream<Result<char, MarbleError>> {
+    marbles_with(diagram, |c| c)
+}
+
+///
+/// Same as [`marbles`], but every item character is converted to a value using `f`.
+///
+pub fn marbles_with<T, F>(diagram: &str, mut f: F) -> ScriptedStream<Result<T, MarbleError>>
+where
+    F: FnMut(char) -> T,
+{
+    parse_marbles(diagram, |c| match c {
+        '#' => Err(MarbleError),
+        c => Ok(f(c)),
+    })
+}
+
+///
+/// Build a [`ScriptedStream`] of plain `char` items from a marble diagram (see the
+/// [module documentation](self)).
+///
+/// # Panics
+///
+/// If the diagram contains an error (`#`).
+///
+pub fn marble_items(diagram: &str) -> ScriptedStream<char> {
+    parse_marbles(diagram, |c| {
+        assert!(c != '#', "marble_items diagram can't contain errors: {:?}", diagram);
+        c
+    })
+}
+
+fn parse_marbles<T, F>(diagram: &str, mut item: F) -> ScriptedStream<T>
+where
+    F: FnMut(char) -> T,
+{
+    let mut steps = Vec::new();
+    let mut completes = false;
+    for c in diagram.chars().filter(|c| !c.is_whitespace()) {
+        assert!(!completes, "marble diagram continues after completion: {:?}", diagram);
+        match c {
+            '-' => steps.push(Step::Pending),
+            '|' => completes = true,
+            c => steps.push(Step::Item(item(c))),
+        }
+    }
+
+    let stream = ScriptedStream::new(steps);
+    if completes {
+        stream
+    } else {
+        stream.without_completion()
+    }
+}
+
+///
+/// Something which can be drawn in a marble diagram (see the [module documentation](self)).
+///
+pub trait Marble {
+    /// The character which represents this value
+    fn to_marble(&self) -> char;
+}
+
+impl Marble for char {
+    fn to_marble(&self) -> char {
+        *self
+    }
+}
+
+impl<T, E> Marble for Result<T, E>
+where
+    T: Marble,
+{
+    fn to_marble(&self) -> char {
+        match self {
+            Ok(v) => v.to_marble(),
+            Err(_) => '#',
+        }
+    }
+}
+
+///
+/// Poll the stream until it completes (or stops waking the task), and draw everything it did as a
+/// marble diagram (see the [module documentation](self)).
+///
+pub fn draw_marbles<S>(stream: &mut S) -> String
+where
+    S: Stream + Unpin,
+    S::Item: Marble,
+{
+    let waker = CountingWaker::new();
+    let mut out = String::new();
+    let mut consecutive_pending = 0;
+    loop {
+        let wakes_before = waker.count();
+        match poll_once(stream, &waker) {
+            Poll::Ready(Some(item)) => {
+                consecutive_pending = 0;
+                out.push(item.to_marble());
+            }
+            Poll::Ready(None) => {
+                out.push('|');
+                break out;
+            }
+            // nothing will ever wake us up, so this is as far as the stream gets
+            Poll::Pending if waker.count() == wakes_before => break out,
+            Poll::Pending => {
+                consecutive_pending += 1;
+                assert!(
+                    consecutive_pending < MAX_CONSECUTIVE_PENDING,
+                    "stream returned Poll::Pending {} times in a row",
+                    MAX_CONSECUTIVE_PENDING
+                );
+                out.push('-');
+            }
+        }
+    }
+}
+
+///
+/// Assert that the stream behaves exactly as described by the `expected` marble diagram (see the
+/// [module documentation](self)), including any `Poll::Pending` gaps.
+///
+pub fn assert_marbles<S>(stream: &mut S, expected: &str)
+where
+    S: Stream + Unpin,
+    S::Item: Marble,
+{
+    let expected: String = expected.chars().filter(|c| !c.is_whitespace()).collect();
+    let actual = draw_marbles(stream);
+    assert_eq!(actual, expected, "stream did not match marble diagram");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        assert_marbles, assert_stream_yields, draw_marbles, marble_items, marbles, marbles_with,
+        poll_once, poll_to_end, CountingWaker, MarbleError, ScriptedStream, Step,
+    };
+    use futures::executor::block_on;
+    use futures::StreamExt;
+    use futures::stream::FusedStream;
+    use std::task::Poll;
+
+    #[test]
+    fn test_scripted_stream() {
+        let mut stream = ScriptedStream::new(vec![Step::Item(1), Step::Pending, Step::Item(2)]);
+        let handle = stream.handle();
+        let waker = CountingWaker::new();
+
+        assert_eq!(poll_once(&mut stream, &waker), Poll::Ready(Some(1)));
+        assert_eq!(poll_once(&mut stream, &waker), Poll::Pending);
+        assert_eq!(waker.count(), 1);
+        assert_eq!(poll_once(&mut stream, &waker), Poll::Ready(Some(2)));
+        assert!(!stream.is_terminated());
+        assert_eq!(poll_once(&mut stream, &waker), Poll::Ready(None));
+        assert!(stream.is_terminated());
+        assert_eq!(handle.polls_after_end(), 0);
+        assert_eq!(poll_once(&mut stream, &waker), Poll::Ready(None));
+        assert_eq!(handle.polls(), 5);
+        assert_eq!(handle.polls_after_end(), 1);
+    }
+
+    #[test]
+    fn test_poll_to_end() {
+        let mut stream = ScriptedStream::interleaved(vec!["a", "b"]);
+        assert_eq!(
+            poll_to_end(&mut stream),
+            vec![Step::Pending, Step::Item("a"), Step::Pending, Step::Item("b"), Step::Pending]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "without waking the task")]
+    fn test_poll_to_end_detects_lost_wakeup() {
+        let mut stream = futures::stream::pending::<()>();
+        poll_to_end(&mut stream);
+    }
+
+    #[test]
+    fn test_assert_stream_yields() {
+        assert_stream_yields(&mut ScriptedStream::interleaved(1..=3), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_marbles() {
+        let stream = marbles("a-b-#|");
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(items, vec![Ok('a'), Ok('b'), Err(MarbleError)]);
+
+        let stream = marbles_with("1 2 3|", |c| c.to_digit(10).unwrap());
+        let items: Vec<_> = block_on(stream.collect());
+        assert_eq!(items, vec![Ok(1), Ok(2), Ok(3)]);
+    }
+
+    #[test]
+    fn test_draw_marbles() {
+        assert_eq!(draw_marbles(&mut marbles("a--b-#|")), "a--b-#|");
+        assert_eq!(draw_marbles(&mut marble_items("-ab")), "-ab");
+        assert_eq!(draw_marbles(&mut marble_items("")), "");
+        assert_marbles(&mut marble_items("a - b |"), "a-b|");
+    }
+
+    #[test]
+    #[should_panic(expected = "did not match marble diagram")]
+    fn test_assert_marbles_mismatch() {
+        assert_marbles(&mut marble_items("a-b|"), "ab|");
+    }
+}
@@ -0,0 +1,276 @@
+use crate::op_prelude::*;
+use crate::time::{yield_now, Timer, POLL_BUDGET};
+use std::time::Duration;
+
+pin_project! {
+    /// Stream for the [`throttle`](super::JStreamExt::throttle) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Throttle<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        duration: Duration,
+        leading: bool,
+        trailing: bool,
+        #[pin]
+        window: Option<T::Delay>,
+        held: Option<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for Throttle<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let mut taken = 0;
+        loop {
+            if let Some(window) = this.window.as_mut().as_pin_mut() {
+                if window.poll(cx).is_ready() {
+                    this.window.set(None);
+                    if let Some(held) = this.held.take() {
+                        // the trailing item starts a window of its own
+                        if !*this.src_done {
+                            this.window.set(Some(this.timer.delay(*this.duration)));
+                        }
+                        return Poll::Ready(Some(held));
+                    }
+                }
+            }
+
+            if *this.src_done {
+                if this.held.is_none() {
+                    this.window.set(None);
+                    return Poll::Ready(None);
+                }
+
+                // the held item is emitted once the window closes
+                return Poll::Pending;
+            }
+
+            // items which arrive while a window is open are dropped, so don't spin on them forever
+            if taken == POLL_BUDGET {
+                return yield_now(cx);
+            }
+
+            match ready!(this.src.as_mut().poll_next(cx)) {
+                Some(next) => {
+                    taken += 1;
+                    if this.window.is_none() {
+                        this.window.set(Some(this.timer.delay(*this.duration)));
+                        if *this.leading {
+                            return Poll::Ready(Some(next));
+                        }
+                    }
+
+                    if *this.trailing {
+                        *this.held = Some(next);
+                    }
+                }
+                None => *this.src_done = true,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let held = if self.held.is_some() { 1 } else { 0 };
+        if self.src_done {
+            (held, Some(held))
+        } else {
+            (held, self.src.size_hint().1.and_then(|upper| upper.checked_add(held)))
+        }
+    }
+}
+
+impl<S, T> FusedStream for Throttle<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.held.is_none()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for Throttle<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> Throttle<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            duration,
+            leading: true,
+            trailing: false,
+            window: None,
+            held: None,
+            src_done: false,
+        }
+    }
+
+    ///
+    /// Whether the item which opens a window is emitted right away. The default is `true`.
+    ///
+    /// At least one of `leading` and `trailing` is always enabled, because otherwise every item
+    /// would be dropped. Passing `false` here also enables [`trailing`](Throttle::trailing).
+    ///
+    pub fn leading(mut self, leading: bool) -> Self {
+        self.leading = leading;
+        if !leading {
+            self.trailing = true;
+        }
+        self
+    }
+
+    ///
+    /// Whether the last item received during a window is emitted when the window closes (which
+    /// then opens a new window). The default is `false`.
+    ///
+    /// If the source stream completes while a trailing item is held, it is still emitted once the
+    /// window closes.
+    ///
+    /// At least one of `leading` and `trailing` is always enabled, because otherwise every item
+    /// would be dropped. Passing `false` here also enables [`leading`](Throttle::leading).
+    ///
+    pub fn trailing(mut self, trailing: bool) -> Self {
+        self.trailing = trailing;
+        if !trailing {
+            self.leading = true;
+        }
+        self
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> Throttle<S, T2> {
+        let mut throttle = Throttle::new(self.src, self.duration, timer);
+        throttle.leading = self.leading;
+        throttle.trailing = self.trailing;
+        throttle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Throttle;
+    use crate::test_util::TimedHarness;
+    use futures::stream;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_throttle_leading() {
+        let mut harness = TimedHarness::new();
+        let mut throttled = Throttle::new(
+            harness.source(),
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        harness.send(1);
+        harness.send(2);
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+
+        harness.advance(Duration::from_secs(1));
+        harness.send(3);
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(3)));
+        harness.send(4);
+        harness.close();
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_throttle_trailing() {
+        let mut harness = TimedHarness::new();
+        let mut throttled = Throttle::new(harness.source(), Duration::from_secs(1), harness.clock())
+            .leading(false)
+            .trailing(true);
+
+        harness.send(1);
+        harness.send(2);
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+        harness.advance(Duration::from_millis(500));
+        harness.send(3);
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+
+        harness.advance(Duration::from_millis(500));
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(3)));
+
+        // the trailing item opened a new window, which still holds items back after the end
+        harness.send(4);
+        harness.close();
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(4)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_throttle_leading_and_trailing() {
+        let mut harness = TimedHarness::new();
+        let mut throttled = Throttle::new(harness.source(), Duration::from_secs(1), harness.clock())
+            .trailing(true);
+
+        harness.send(1);
+        harness.send(2);
+        harness.send(3);
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(3)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+    }
+
+    #[test]
+    fn test_throttle_never_drops_everything() {
+        let mut harness = TimedHarness::new();
+        let mut throttled = Throttle::new(harness.source(), Duration::from_secs(1), harness.clock())
+            .leading(false)
+            .trailing(false);
+        assert!(throttled.leading && !throttled.trailing);
+
+        harness.send(1);
+        harness.send(2);
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+
+        // the last call wins, and keeps the other one enabled
+        let src = futures::stream::empty::<()>();
+        let throttled = Throttle::new(src, Duration::from_secs(1), harness.clock())
+            .trailing(false)
+            .leading(false);
+        assert!(!throttled.leading && throttled.trailing);
+    }
+
+    #[test]
+    fn test_throttle_always_ready_source() {
+        let harness: TimedHarness = TimedHarness::new();
+        let second = Duration::from_secs(1);
+        let mut throttled = Throttle::new(stream::repeat(1), second, harness.clock());
+
+        // the first item opens the window, and the rest are dropped a budget at a time
+        assert_eq!(harness.poll(&mut throttled), Poll::Ready(Some(1)));
+        assert_eq!(harness.poll(&mut throttled), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+    }
+}
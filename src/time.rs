@@ -0,0 +1,449 @@
+//!
+//! A runtime-agnostic notion of time, used by the timed combinators in this crate.
+//!
+//! This crate does not depend on any particular async runtime. Instead, anything which needs to
+//! know the current time or wait for some time to pass does so through the [`Clock`] and [`Timer`]
+//! traits.
+//!
+//! [`VirtualClock`] is a [`Timer`] which only moves forward when you tell it to, which allows
+//! time-based behavior to be tested deterministically with `futures::executor::block_on` and
+//! without any real sleeping:
+//!
+//! ```
+//! use futures::executor::block_on;
+//! use jstream_ext::time::{Timer, VirtualClock};
+//! use std::time::Duration;
+//!
+//! let clock = VirtualClock::new();
+//! let delay = clock.delay(Duration::from_secs(60));
+//! clock.advance(Duration::from_secs(60));
+//! block_on(delay); // completes immediately
+//! ```
+//!
+//! # Runtime Timers
+//!
+//! The following real [`Timer`] implementations are provided:
+//!
+//! * [`TokioTimer`] - uses `tokio::time`, only available with the `tokio` feature
+//! * [`AsyncStdTimer`] - uses `async_std::task::sleep`, only available with the `async-std` feature
+//! * [`ThreadTimer`] - a pure-std timer (and the default) which tracks deadlines on a background thread
+//!
+//! The timed combinators in this crate use [`DefaultTimer`] (which is always [`ThreadTimer`])
+//! unless told otherwise. Enabling the `tokio` or `async-std` feature only makes the matching timer
+//! available, it never changes the default, so a runtime timer has to be passed to `with_timer`
+//! explicitly.
+//!
+
+use futures::Future;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async-std")]
+mod async_std_timer;
+mod thread_timer;
+#[cfg(feature = "tokio")]
+mod tokio_timer;
+
+#[cfg(feature = "async-std")]
+pub use async_std_timer::AsyncStdTimer;
+pub use thread_timer::{ThreadDelay, ThreadTimer};
+#[cfg(feature = "tokio")]
+pub use tokio_timer::TokioTimer;
+
+///
+/// The [`Timer`] used by the timed combinators in this crate, unless another one is specified.
+///
+/// This is always [`ThreadTimer`], which works with any executor. It does not depend on which
+/// features are enabled, because features are shared by every crate in the dependency graph.
+///
+pub type DefaultTimer = ThreadTimer;
+
+///
+/// Something which can tell the current time.
+///
+pub trait Clock {
+    /// The current time according to this clock
+    fn now(&self) -> Instant;
+}
+
+///
+/// A [`Clock`] which can also create futures that complete at some point in time.
+///
+pub trait Timer: Clock {
+    /// Future which completes once the deadline has been reached
+    type Delay: Future<Output = ()>;
+
+    /// Create a future which completes once [`now`](Clock::now) reaches `deadline`
+    fn delay_until(&self, deadline: Instant) -> Self::Delay;
+
+    /// Create a future which completes once `duration` has passed
+    ///
+    /// If `duration` is too long to be added to [`now`](Clock::now) (such as `Duration::MAX`), the
+    /// delay is set to a deadline far enough in the future that it will never complete.
+    fn delay(&self, duration: Duration) -> Self::Delay {
+        self.delay_until(deadline_after(self.now(), duration))
+    }
+}
+
+///
+/// A manually driven [`Timer`], for tests.
+///
+/// The clock starts at the (real) time it was created, and only moves forward when
+/// [`advance`](VirtualClock::advance) or [`advance_to`](VirtualClock::advance_to) is called. Any
+/// delays whose deadlines are reached are woken up at that point.
+///
+/// Cloning a `VirtualClock` gives another handle to the same clock.
+///
+#[derive(Clone)]
+pub struct VirtualClock {
+    state: Arc<Mutex<VirtualClockState>>,
+}
+
+struct VirtualClockState {
+    now: Instant,
+    next_id: u64,
+    sleepers: HashMap<u64, (Instant, Waker)>,
+}
+
+impl VirtualClock {
+    /// Create a new clock, stopped at the current time
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    /// Create a new clock, stopped at the given time
+    pub fn starting_at(now: Instant) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(VirtualClockState {
+                now,
+                next_id: 0,
+                sleepers: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Move the clock forward by `duration`, waking any delays which are now complete
+    pub fn advance(&self, duration: Duration) {
+        let now = self.now();
+        self.advance_to(now + duration);
+    }
+
+    ///
+    /// Move the clock forward to `instant`, waking any delays which are now complete. If `instant`
+    /// is in the past, the clock is not changed.
+    ///
+    pub fn advance_to(&self, instant: Instant) {
+        let mut woken = Vec::new();
+        {
+            let mut state = lock(&self.state);
+            if instant <= state.now {
+                return;
+            }
+
+            state.now = instant;
+            state.sleepers.retain(|_, (deadline, waker)| {
+                if *deadline <= instant {
+                    woken.push(waker.clone());
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        // wake outside of the lock, in case a waker polls the delay right away
+        for waker in woken {
+            waker.wake();
+        }
+    }
+
+    ///
+    /// Move the clock forward to the earliest deadline of any delay which is waiting on this clock.
+    /// Returns `false` (and does nothing) if no delays are waiting.
+    ///
+    pub fn advance_to_next(&self) -> bool {
+        match self.next_deadline() {
+            Some(deadline) => {
+                self.advance_to(deadline);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The earliest deadline of any delay which has been polled and is waiting on this clock
+    pub fn next_deadline(&self) -> Option<Instant> {
+        lock(&self.state)
+            .sleepers
+            .values()
+            .map(|(deadline, _)| *deadline)
+            .min()
+    }
+
+    /// The number of delays which have been polled and are waiting on this clock
+    pub fn pending_delays(&self) -> usize {
+        lock(&self.state).sleepers.len()
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        lock(&self.state).now
+    }
+}
+
+impl Timer for VirtualClock {
+    type Delay = VirtualDelay;
+
+    fn delay_until(&self, deadline: Instant) -> VirtualDelay {
+        VirtualDelay {
+            state: self.state.clone(),
+            deadline,
+            id: None,
+        }
+    }
+}
+
+///
+/// Future returned by the [`Timer`] implementation of [`VirtualClock`]
+///
+#[must_use = "futures do nothing unless polled"]
+pub struct VirtualDelay {
+    state: Arc<Mutex<VirtualClockState>>,
+    deadline: Instant,
+    id: Option<u64>,
+}
+
+impl Future for VirtualDelay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut state = lock(&this.state);
+        if state.now >= this.deadline {
+            if let Some(id) = this.id.take() {
+                state.sleepers.remove(&id);
+            }
+            return Poll::Ready(());
+        }
+
+        let id = *this.id.get_or_insert_with(|| {
+            state.next_id += 1;
+            state.next_id
+        });
+        state.sleepers.insert(id, (this.deadline, cx.waker().clone()));
+        Poll::Pending
+    }
+}
+
+impl Drop for VirtualDelay {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            lock(&self.state).sleepers.remove(&id);
+        }
+    }
+}
+
+// How many items a timed stream takes from its upstream in a single poll. Without a limit, an
+// upstream which is always ready (such as `stream::repeat`) would keep `poll_next` from returning.
+pub(crate) const POLL_BUDGET: usize = 64;
+
+// Give control back to the executor, and ask it to poll this task again once it's done so
+pub(crate) fn yield_now<T>(cx: &mut Context<'_>) -> Poll<T> {
+    cx.waker().wake_by_ref();
+    Poll::Pending
+}
+
+// Roughly 30 years, which no program is expected to run for
+const FAR_FUTURE: Duration = Duration::from_secs(60 * 60 * 24 * 365 * 30);
+
+// `now + duration`, or a point in time which will never be reached if that can't be represented
+pub(crate) fn deadline_after(now: Instant, duration: Duration) -> Instant {
+    now.checked_add(duration)
+        .or_else(|| now.checked_add(FAR_FUTURE))
+        .unwrap_or(now)
+}
+
+// the first boundary after `now`, in steps of `step` starting from `boundary`
+pub(crate) fn skip_to(boundary: Instant, now: Instant, step: Duration) -> Instant {
+    if now < boundary {
+        return boundary;
+    }
+
+    let steps = now.duration_since(boundary).as_nanos() / step.as_nanos() + 1;
+    let skipped = step.checked_mul(u32::try_from(steps).unwrap_or(u32::MAX));
+    deadline_after(boundary, skipped.unwrap_or(Duration::MAX))
+}
+
+pin_project_lite::pin_project! {
+    // Tracks how long an upstream has been idle, for streams which emit something when it has
+    // been idle for too long (such as timeout_each and heartbeat).
+    pub(crate) struct IdleTimer<T>
+    where
+        T: Timer,
+    {
+        timer: T,
+        duration: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        src_done: bool,
+    }
+}
+
+pub(crate) enum IdleEvent<I> {
+    Item(I),
+    Idle,
+}
+
+impl<T: Timer> IdleTimer<T> {
+    pub(crate) fn new(duration: Duration, timer: T) -> Self {
+        Self {
+            timer,
+            duration,
+            delay: None,
+            src_done: false,
+        }
+    }
+
+    pub(crate) fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.src_done
+    }
+
+    // Poll the upstream using `poll_src`, and give its next item, or `Idle` once `duration` has
+    // passed without one. The upstream always goes first, so an item which is ready when the timer
+    // fires wins.
+    //
+    // The timer is started by the first poll which finds the upstream pending after an item (or
+    // after the last `Idle`), so the idle time counts from that poll, not from when the item was
+    // emitted.
+    pub(crate) fn poll_next<I>(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        poll_src: impl FnOnce(&mut Context<'_>) -> Poll<Option<I>>,
+    ) -> Poll<Option<IdleEvent<I>>> {
+        let mut this = self.project();
+        if *this.src_done {
+            return Poll::Ready(None);
+        }
+
+        if let Poll::Ready(next) = poll_src(cx) {
+            this.delay.set(None);
+            if next.is_none() {
+                *this.src_done = true;
+            }
+            return Poll::Ready(next.map(IdleEvent::Item));
+        }
+
+        if this.delay.is_none() {
+            this.delay.set(Some(this.timer.delay(*this.duration)));
+        }
+
+        ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+        this.delay.set(None);
+        Poll::Ready(Some(IdleEvent::Idle))
+    }
+
+    // any number of idle events can be emitted between items
+    pub(crate) fn size_hint(&self, (src_lower, _): (usize, Option<usize>)) -> (usize, Option<usize>) {
+        if self.src_done {
+            (0, Some(0))
+        } else {
+            (src_lower, None)
+        }
+    }
+}
+
+fn lock(state: &Mutex<VirtualClockState>) -> MutexGuard<'_, VirtualClockState> {
+    state.lock().expect("virtual clock state poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clock, Timer, VirtualClock};
+    use crate::test_util::CountingWaker;
+    use futures::FutureExt;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    #[test]
+    fn test_virtual_clock_advance() {
+        let clock = VirtualClock::new();
+        let start = clock.now();
+        let waker = CountingWaker::new();
+        let task_waker = waker.waker();
+        let mut cx = Context::from_waker(&task_waker);
+
+        let mut delay = clock.delay(Duration::from_secs(10));
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+        assert_eq!(clock.pending_delays(), 1);
+        assert_eq!(clock.next_deadline(), Some(start + Duration::from_secs(10)));
+
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(waker.count(), 0);
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(waker.count(), 1);
+        assert_eq!(clock.pending_delays(), 0);
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Ready(()));
+        assert_eq!(clock.now(), start + Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_virtual_clock_advance_to_next() {
+        let clock = VirtualClock::new();
+        let waker = CountingWaker::new();
+        let task_waker = waker.waker();
+        let mut cx = Context::from_waker(&task_waker);
+
+        let mut first = clock.delay(Duration::from_secs(5));
+        let mut second = clock.delay(Duration::from_secs(7));
+        assert!(first.poll_unpin(&mut cx).is_pending());
+        assert!(second.poll_unpin(&mut cx).is_pending());
+
+        assert!(clock.advance_to_next());
+        assert!(first.poll_unpin(&mut cx).is_ready());
+        assert!(second.poll_unpin(&mut cx).is_pending());
+
+        drop(second);
+        assert!(!clock.advance_to_next());
+    }
+
+    #[test]
+    fn test_virtual_clock_never_goes_back() {
+        let clock = VirtualClock::new();
+        let start = clock.now();
+        clock.advance(Duration::from_secs(1));
+        clock.advance_to(start);
+        assert_eq!(clock.now(), start + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_delay_overflow() {
+        let clock = VirtualClock::new();
+        let waker = CountingWaker::new();
+        let task_waker = waker.waker();
+        let mut cx = Context::from_waker(&task_waker);
+
+        // a duration which can't be added to now is a delay which never completes
+        let mut delay = clock.delay(Duration::MAX);
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+        clock.advance(Duration::from_secs(60 * 60 * 24 * 365));
+        assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+        assert_eq!(waker.count(), 0);
+    }
+}
@@ -0,0 +1,47 @@
+use super::{Clock, Timer};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::time::Instant;
+
+///
+/// A [`Timer`] backed by [`async_std::task::sleep`].
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AsyncStdTimer;
+
+impl AsyncStdTimer {
+    /// Create a new timer which uses async-std
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clock for AsyncStdTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl Timer for AsyncStdTimer {
+    type Delay = BoxFuture<'static, ()>;
+
+    fn delay_until(&self, deadline: Instant) -> Self::Delay {
+        let duration = deadline.saturating_duration_since(Instant::now());
+        async_std::task::sleep(duration).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsyncStdTimer;
+    use crate::time::{Clock, Timer};
+    use std::time::Duration;
+
+    #[test]
+    fn test_async_std_timer() {
+        let timer = AsyncStdTimer::new();
+        let start = timer.now();
+        async_std::task::block_on(timer.delay(Duration::from_millis(10)));
+        assert!(timer.now() - start >= Duration::from_millis(10));
+    }
+}
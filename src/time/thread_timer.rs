@@ -0,0 +1,245 @@
+use super::{Clock, Timer};
+use futures::Future;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, Weak};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Instant;
+
+///
+/// A [`Timer`] which doesn't depend on any async runtime.
+///
+/// All delays are tracked by a single background thread, which is started the first time a delay
+/// is polled, and sleeps until the next deadline.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadTimer;
+
+impl ThreadTimer {
+    /// Create a new handle to the shared background timer thread
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clock for ThreadTimer {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl Timer for ThreadTimer {
+    type Delay = ThreadDelay;
+
+    fn delay_until(&self, deadline: Instant) -> ThreadDelay {
+        ThreadDelay { deadline, entry: None }
+    }
+}
+
+///
+/// Future returned by the [`Timer`] implementation of [`ThreadTimer`]
+///
+#[must_use = "futures do nothing unless polled"]
+pub struct ThreadDelay {
+    deadline: Instant,
+    entry: Option<Arc<Entry>>,
+}
+
+#[derive(Default)]
+struct Entry {
+    fired: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Future for ThreadDelay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        let deadline = self.deadline;
+        let entry = self.entry.get_or_insert_with(|| {
+            let entry = Arc::new(Entry::default());
+            worker().schedule(deadline, Arc::downgrade(&entry));
+            entry
+        });
+
+        *entry.waker.lock().expect("timer entry poisoned") = Some(cx.waker().clone());
+        // the worker may have fired between the check above and storing the waker
+        if entry.fired.load(AtomicOrdering::SeqCst) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for ThreadDelay {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            if !entry.fired.load(AtomicOrdering::SeqCst) {
+                // drop our handle first, so that the worker sees the entry as dead when compacting
+                drop(entry);
+                worker().cancel();
+            }
+        }
+    }
+}
+
+struct Scheduled {
+    deadline: Instant,
+    entry: Weak<Entry>,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    // reversed, so that the BinaryHeap gives us the earliest deadline
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+struct Worker {
+    queue: Mutex<Queue>,
+    condvar: Condvar,
+}
+
+#[derive(Default)]
+struct Queue {
+    heap: BinaryHeap<Scheduled>,
+    // roughly how many entries in the heap belong to delays which were dropped before they fired
+    cancelled: usize,
+}
+
+fn worker() -> &'static Worker {
+    static WORKER: OnceLock<&'static Worker> = OnceLock::new();
+    WORKER.get_or_init(|| {
+        let worker: &'static Worker = Box::leak(Box::new(Worker {
+            queue: Mutex::new(Queue::default()),
+            condvar: Condvar::new(),
+        }));
+
+        thread::Builder::new()
+            .name("jstream-ext-timer".to_owned())
+            .spawn(move || worker.run())
+            .expect("failed to spawn timer thread");
+
+        worker
+    })
+}
+
+impl Worker {
+    fn schedule(&self, deadline: Instant, entry: Weak<Entry>) {
+        self.queue
+            .lock()
+            .expect("timer queue poisoned")
+            .heap
+            .push(Scheduled { deadline, entry });
+        self.condvar.notify_one();
+    }
+
+    // Dropped delays would otherwise stay in the heap until their deadline, so once they outnumber
+    // the live ones, they are all removed at once.
+    fn cancel(&self) {
+        let mut queue = self.queue.lock().expect("timer queue poisoned");
+        queue.cancelled += 1;
+        if queue.cancelled > queue.heap.len() / 2 {
+            queue.heap.retain(|next| next.entry.strong_count() > 0);
+            queue.cancelled = 0;
+        }
+    }
+
+    fn run(&self) {
+        let mut queue = self.queue.lock().expect("timer queue poisoned");
+        loop {
+            let now = Instant::now();
+            let mut fired = Vec::new();
+            while queue.heap.peek().is_some_and(|next| next.deadline <= now) {
+                match queue.heap.pop().and_then(|next| next.entry.upgrade()) {
+                    Some(entry) => fired.push(entry),
+                    None => queue.cancelled = queue.cancelled.saturating_sub(1),
+                }
+            }
+
+            if !fired.is_empty() {
+                drop(queue);
+                for entry in fired {
+                    entry.fired.store(true, AtomicOrdering::SeqCst);
+                    let waker = entry.waker.lock().expect("timer entry poisoned").take();
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+                queue = self.queue.lock().expect("timer queue poisoned");
+                continue;
+            }
+
+            queue = match queue.heap.peek().map(|next| next.deadline) {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    self.condvar
+                        .wait_timeout(queue, timeout)
+                        .expect("timer queue poisoned")
+                        .0
+                }
+                None => self.condvar.wait(queue).expect("timer queue poisoned"),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{worker, ThreadTimer};
+    use crate::time::{Clock, Timer};
+    use futures::executor::block_on;
+    use futures::task::noop_waker_ref;
+    use futures::FutureExt;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    #[test]
+    fn test_thread_timer() {
+        let timer = ThreadTimer::new();
+        let start = timer.now();
+        block_on(futures::future::join(
+            timer.delay(Duration::from_millis(20)),
+            timer.delay(Duration::from_millis(10)),
+        ));
+        assert!(timer.now() - start >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_thread_timer_reset() {
+        let timer = ThreadTimer::new();
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        // restarting a delay (like debounce does for every item) mustn't leave the old ones behind
+        for _ in 0..1000 {
+            let mut delay = timer.delay(Duration::from_secs(3600));
+            assert_eq!(delay.poll_unpin(&mut cx), Poll::Pending);
+        }
+
+        // other tests may have delays of their own scheduled at the same time
+        let scheduled = worker().queue.lock().expect("timer queue poisoned").heap.len();
+        assert!(scheduled < 100, "{} delays are still scheduled", scheduled);
+    }
+}
@@ -0,0 +1,51 @@
+use super::{Clock, Timer};
+use std::time::Instant;
+
+///
+/// A [`Timer`] backed by [`tokio::time`], which must be used from within a tokio runtime with the
+/// time driver enabled.
+///
+/// This respects tokio's paused time (`tokio::time::pause`), so it can also be used in tests.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioTimer;
+
+impl TokioTimer {
+    /// Create a new timer which uses the current tokio runtime
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clock for TokioTimer {
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+impl Timer for TokioTimer {
+    type Delay = tokio::time::Sleep;
+
+    fn delay_until(&self, deadline: Instant) -> Self::Delay {
+        tokio::time::sleep_until(deadline.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TokioTimer;
+    use crate::time::{Clock, Timer};
+    use std::time::Duration;
+
+    #[test]
+    fn test_tokio_timer() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let timer = TokioTimer::new();
+        let start = timer.now();
+        runtime.block_on(async { timer.delay(Duration::from_millis(10)).await });
+        assert!(timer.now() - start >= Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,239 @@
+use crate::op_prelude::*;
+use crate::time::{IdleEvent, IdleTimer, Timer};
+use std::fmt;
+use std::time::Duration;
+
+///
+/// Error emitted by [`timeout_each`](super::JStreamExt::timeout_each) when the upstream takes too
+/// long to produce an item.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Elapsed {
+    duration: Duration,
+}
+
+impl Elapsed {
+    /// The duration which was exceeded
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no item was received within {:?}", self.duration)
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+pin_project! {
+    /// Stream for the [`timeout_each`](super::JStreamExt::timeout_each) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TimeoutEach<S, T>
+    where
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        #[pin]
+        idle: IdleTimer<T>,
+    }
+}
+
+impl<S, T> Stream for TimeoutEach<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = Result<S::Item, Elapsed>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let src = this.src;
+        let duration = this.idle.duration();
+        let next = ready!(this.idle.poll_next(cx, |cx| src.poll_next(cx)));
+        Poll::Ready(next.map(|event| match event {
+            IdleEvent::Item(item) => Ok(item),
+            IdleEvent::Idle => Err(Elapsed { duration }),
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.idle.size_hint(self.src.size_hint())
+    }
+}
+
+impl<S, T> FusedStream for TimeoutEach<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.idle.is_done()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for TimeoutEach<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> TimeoutEach<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            idle: IdleTimer::new(duration, timer),
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> TimeoutEach<S, T2> {
+        TimeoutEach::new(self.src, self.idle.duration(), timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_timeout_each`](super::JTryStreamExt::try_timeout_each) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryTimeoutEach<S, T, F>
+    where
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        #[pin]
+        idle: IdleTimer<T>,
+        on_timeout: F,
+    }
+}
+
+impl<S, T, F> Stream for TryTimeoutEach<S, T, F>
+where
+    S: TryStream,
+    T: Timer,
+    F: FnMut() -> S::Error,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let src = this.src;
+        let on_timeout = this.on_timeout;
+        let next = ready!(this.idle.poll_next(cx, |cx| src.try_poll_next(cx)));
+        Poll::Ready(next.map(|event| match event {
+            IdleEvent::Item(item) => item,
+            IdleEvent::Idle => Err(on_timeout()),
+        }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.idle.size_hint(self.src.size_hint())
+    }
+}
+
+impl<S, T, F> FusedStream for TryTimeoutEach<S, T, F>
+where
+    S: TryStream,
+    T: Timer,
+    F: FnMut() -> S::Error,
+{
+    fn is_terminated(&self) -> bool {
+        self.idle.is_done()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, F, Item, E> Sink<Item> for TryTimeoutEach<S, T, F>
+where
+    S: TryStream + Sink<Item, Error=E>,
+    T: Timer,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, T, F> TryTimeoutEach<S, T, F>
+where
+    S: TryStream,
+    T: Timer,
+    F: FnMut() -> S::Error,
+{
+    pub(crate) fn new(src: S, duration: Duration, on_timeout: F, timer: T) -> Self {
+        Self {
+            src,
+            idle: IdleTimer::new(duration, timer),
+            on_timeout,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> TryTimeoutEach<S, T2, F> {
+        TryTimeoutEach::new(self.src, self.idle.duration(), self.on_timeout, timer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Elapsed, TimeoutEach, TryTimeoutEach};
+    use crate::test_util::TimedHarness;
+    use crate::JTryStreamExt;
+    use futures::stream::FusedStream;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_timeout_each() {
+        let mut harness = TimedHarness::new();
+        let duration = Duration::from_secs(1);
+        let mut timed = TimeoutEach::new(harness.source(), duration, harness.clock());
+
+        harness.send("a");
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(Some(Ok("a"))));
+        assert_eq!(harness.poll(&mut timed), Poll::Pending);
+        harness.advance(Duration::from_millis(999));
+        harness.send("b");
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(Some(Ok("b"))));
+
+        // the timer restarted when "b" arrived, and restarts again after each timeout
+        assert_eq!(harness.poll(&mut timed), Poll::Pending);
+        harness.advance(duration);
+        assert_eq!(
+            harness.poll(&mut timed),
+            Poll::Ready(Some(Err(Elapsed { duration })))
+        );
+        assert_eq!(harness.poll(&mut timed), Poll::Pending);
+        harness.advance(duration);
+        assert!(matches!(harness.poll(&mut timed), Poll::Ready(Some(Err(_)))));
+
+        harness.close();
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_try_timeout_each_fuse_on_fail() {
+        let mut harness = TimedHarness::<Result<&str, &str>>::new();
+        let mut timed = TryTimeoutEach::new(
+            harness.source(),
+            Duration::from_secs(1),
+            || "stalled",
+            harness.clock(),
+        )
+        .fuse_on_fail();
+
+        harness.send(Ok("a"));
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(Some(Ok("a"))));
+        assert_eq!(harness.poll(&mut timed), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(Some(Err("stalled"))));
+        assert_eq!(harness.poll(&mut timed), Poll::Ready(None));
+        assert!(timed.is_terminated());
+    }
+}
@@ -1,4 +1,6 @@
 use crate::op_prelude::*;
+use futures::stream::FuturesOrdered;
+use futures::StreamExt;
 
 pin_project! {
     /// Stream for the [`try_filter_map_ok`](super::JTryStreamExt::try_filter_map_ok) method
@@ -32,7 +34,8 @@ where
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        self.src.size_hint()
+        // every item could be filtered out
+        (0, self.src.size_hint().1)
     }
 }
 
@@ -67,11 +70,299 @@ where
     }
 }
 
+pin_project! {
+    /// Stream for the [`try_filter_map_ok_fallible`](super::JTryStreamExt::try_filter_map_ok_fallible) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryFilterMapOkFallible<S, F, R> {
+        #[pin]
+        src: S,
+        predicate: F,
+        _rt: PhantomData<R>,
+    }
+}
+
+impl<S, F, R> Stream for TryFilterMapOkFallible<S, F, R>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Result<Option<R>, S::Error>,
+{
+    type Item = Result<R, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => match (this.predicate)(next) {
+                    Ok(Some(out)) => break Some(Ok(out)),
+                    Ok(None) => {}
+                    Err(err) => break Some(Err(err)),
+                }
+                Some(Err(err)) => break Some(Err(err)),
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.src.size_hint().1)
+    }
+}
+
+impl<S, F, R> FusedStream for TryFilterMapOkFallible<S, F, R>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(S::Ok) -> Result<Option<R>, S::Error>,
+{
+    delegate_fused!(src);
+}
+
+#[cfg(feature="sink")]
+impl<S, F, R, Item, E> Sink<Item> for TryFilterMapOkFallible<S, F, R>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F, R> TryFilterMapOkFallible<S, F, R>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Result<Option<R>, S::Error>,
+{
+    pub(crate) fn new(src: S, predicate: F) -> Self {
+        Self {
+            src,
+            predicate,
+            _rt: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_filter_map_ok_async`](super::JTryStreamExt::try_filter_map_ok_async) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryFilterMapOkAsync<S, F, Fut> {
+        #[pin]
+        src: S,
+        predicate: F,
+        #[pin]
+        pending_future: Option<Fut>,
+    }
+}
+
+impl<S, F, Fut, R> Stream for TryFilterMapOkAsync<S, F, Fut>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    type Item = Result<R, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        Poll::Ready(loop {
+            // poll future if we have one
+            if let Some(future) = this.pending_future.as_mut().as_pin_mut() {
+                let out = ready!(future.poll(cx));
+                this.pending_future.set(None);
+                if let Some(out) = out {
+                    break Some(Ok(out));
+                }
+            }
+
+            match ready!(this.src.as_mut().try_poll_next(cx)) {
+                Some(Ok(next)) => this.pending_future.set(Some((this.predicate)(next))),
+                Some(Err(err)) => break Some(Err(err)),
+                None => break None,
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let pending = if self.pending_future.is_some() { 1 } else { 0 };
+        let upper = self.src.size_hint().1.and_then(|upper| upper.checked_add(pending));
+        (0, upper)
+    }
+}
+
+impl<S, F, Fut, R> FusedStream for TryFilterMapOkAsync<S, F, Fut>
+where
+    S: TryStream + FusedStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.pending_future.is_none() && self.src.is_terminated()
+    }
+}
+
+#[cfg(feature="sink")]
+impl<S, F, Fut, Item, E> Sink<Item> for TryFilterMapOkAsync<S, F, Fut>
+where
+    S: TryStream + Sink<Item, Error=E>,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F, Fut, R> TryFilterMapOkAsync<S, F, Fut>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    pub(crate) fn new(src: S, predicate: F) -> Self {
+        Self {
+            src,
+            predicate,
+            pending_future: None,
+        }
+    }
+}
+
+pin_project! {
+    /// Stream for the [`try_filter_map_ok_buffered`](super::JTryStreamExt::try_filter_map_ok_buffered) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryFilterMapOkBuffered<S, F, Fut>
+    where
+        S: TryStream,
+        Fut: Future,
+    {
+        #[pin]
+        src: S,
+        predicate: F,
+        in_flight: FuturesOrdered<Slot<Fut, S::Error>>,
+        limit: usize,
+        src_done: bool,
+    }
+}
+
+impl<S, F, Fut, R> Stream for TryFilterMapOkBuffered<S, F, Fut>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    type Item = Result<R, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            // start as many futures as we're allowed to
+            while !*this.src_done && this.in_flight.len() < *this.limit {
+                match this.src.as_mut().try_poll_next(cx) {
+                    Poll::Ready(Some(Ok(next))) => {
+                        let future = (this.predicate)(next);
+                        this.in_flight.push_back(Slot::future(future));
+                    }
+                    // errors wait in line, so that they're still emitted in order
+                    Poll::Ready(Some(Err(err))) => this.in_flight.push_back(Slot::error(err)),
+                    Poll::Ready(None) => *this.src_done = true,
+                    Poll::Pending => break,
+                }
+            }
+
+            match ready!(this.in_flight.poll_next_unpin(cx)) {
+                Some(Ok(Some(out))) => return Poll::Ready(Some(Ok(out))),
+                Some(Ok(None)) => {}
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                // nothing in flight, so we're either done or waiting on upstream
+                None => return if *this.src_done { Poll::Ready(None) } else { Poll::Pending },
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let in_flight = self.in_flight.len();
+        let upper = if self.src_done {
+            Some(in_flight)
+        } else {
+            self.src.size_hint().1.and_then(|upper| upper.checked_add(in_flight))
+        };
+        (0, upper)
+    }
+}
+
+impl<S, F, Fut, R> FusedStream for TryFilterMapOkBuffered<S, F, Fut>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.in_flight.is_empty()
+    }
+}
+
+#[cfg(feature="sink")]
+impl<S, F, Fut, Item, E> Sink<Item> for TryFilterMapOkBuffered<S, F, Fut>
+where
+    S: TryStream + Sink<Item, Error=E>,
+    Fut: Future,
+{
+    delegate_sink!(src, E, Item);
+}
+
+impl<S, F, Fut, R> TryFilterMapOkBuffered<S, F, Fut>
+where
+    S: TryStream,
+    F: FnMut(S::Ok) -> Fut,
+    Fut: Future<Output = Option<R>>,
+{
+    pub(crate) fn new(src: S, limit: usize, predicate: F) -> Self {
+        Self {
+            src,
+            predicate,
+            in_flight: FuturesOrdered::new(),
+            limit: limit.max(1),
+            src_done: false,
+        }
+    }
+}
+
+pin_project! {
+    /// A single entry in the queue of a [`TryFilterMapOkBuffered`], which is either a running
+    /// future or an error which was emitted by the upstream.
+    struct Slot<Fut, E> {
+        #[pin]
+        future: Option<Fut>,
+        error: Option<E>,
+    }
+}
+
+impl<Fut, E> Slot<Fut, E> {
+    fn future(future: Fut) -> Self {
+        Self { future: Some(future), error: None }
+    }
+
+    fn error(error: E) -> Self {
+        Self { future: None, error: Some(error) }
+    }
+}
+
+impl<Fut, E> Future for Slot<Fut, E>
+where
+    Fut: Future,
+{
+    type Output = Result<Fut::Output, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if let Some(err) = this.error.take() {
+            return Poll::Ready(Err(err));
+        }
+
+        let future = this.future.as_pin_mut().expect("slot polled after completion");
+        future.poll(cx).map(Ok)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::TryFilterMapOk;
+    use super::{TryFilterMapOk, TryFilterMapOkAsync, TryFilterMapOkBuffered, TryFilterMapOkFallible};
+    use crate::test_util::{assert_marbles, assert_stream_yields, marbles, ScriptedStream};
     use futures::executor::block_on;
-    use futures::TryStreamExt;
+    use futures::future::{ready, Ready};
+    use futures::{Stream, TryStreamExt};
 
     #[test]
     fn test_filter_map_ok_simple() {
@@ -94,6 +385,134 @@ mod tests {
         assert_eq!(block_on(raised.try_next()), Err(()));
     }
 
+    #[test]
+    fn test_filter_map_ok_size_hint() {
+        let items: Vec<Result<&str, ()>> = vec![Ok("hello"), Ok(""), Ok("world!")];
+        let mut raised = TryFilterMapOk::new(futures::stream::iter(items), filter_empty);
+        assert_eq!(raised.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("hello".to_owned())));
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("world!".to_owned())));
+        assert_eq!(raised.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_filter_map_ok_async_size_hint() {
+        let items: Vec<Result<&str, ()>> = vec![Ok("hello"), Ok("")];
+        let src = futures::stream::iter(items);
+        let mut raised = TryFilterMapOkAsync::new(src, filter_empty_async);
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("hello".to_owned())));
+        assert_eq!(raised.size_hint(), (0, Some(1)));
+
+        let items: Vec<Result<u32, ()>> = vec![Ok(1), Ok(2), Ok(3)];
+        let mut raised = TryFilterMapOkBuffered::new(futures::stream::iter(items), 2, |n| ready(Some(n)));
+        assert_eq!(raised.size_hint(), (0, Some(3)));
+        assert_eq!(block_on(raised.try_next()), Ok(Some(1)));
+        assert_eq!(raised.size_hint(), (0, Some(2)));
+    }
+
+    #[test]
+    fn test_filter_map_ok_fallible() {
+        let items: Vec<Result<&str, String>> =
+            vec![Ok("1"), Ok(""), Ok("two"), Err("oh no!".to_owned()), Ok("3")];
+        let src = futures::stream::iter(items);
+        let mut raised = TryFilterMapOkFallible::new(src, |v: &str| {
+            if v.is_empty() {
+                Ok(None)
+            } else {
+                v.parse::<u32>().map(Some).map_err(|err| err.to_string())
+            }
+        });
+        assert_eq!(block_on(raised.try_next()), Ok(Some(1)));
+        assert_eq!(block_on(raised.try_next()), Err("invalid digit found in string".to_owned()));
+        assert_eq!(block_on(raised.try_next()), Err("oh no!".to_owned()));
+        assert_eq!(block_on(raised.try_next()), Ok(Some(3)));
+        assert_eq!(block_on(raised.try_next()), Ok(None));
+    }
+
+    #[test]
+    fn test_filter_map_ok_async() {
+        let items: Vec<Result<&str, ()>> = vec![Ok("hello"), Ok(""), Ok("world!"), Err(()), Ok("test 123")];
+        let src = futures::stream::iter(items);
+        let mut raised = TryFilterMapOkAsync::new(src, filter_empty_async);
+        assert_eq!(block_on(raised.try_next()), Ok(Some("hello".to_owned())));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("world!".to_owned())));
+        assert_eq!(block_on(raised.try_next()), Err(()));
+        assert_eq!(block_on(raised.try_next()), Ok(Some("test 123".to_owned())));
+        assert_eq!(block_on(raised.try_next()), Ok(None));
+    }
+
+    #[test]
+    fn test_filter_map_ok_buffered_order() {
+        let items: Vec<Result<u64, ()>> = vec![Ok(30), Ok(0), Ok(10), Err(()), Ok(20)];
+        let src = futures::stream::iter(items);
+
+        // later items finish first, but the output is still in the original order
+        let raised = TryFilterMapOkBuffered::new(src, 3, |n| async move {
+            for _ in 0..n {
+                yield_now().await;
+            }
+            if n == 0 { None } else { Some(n) }
+        });
+
+        let out: Vec<Result<u64, ()>> = block_on(futures::StreamExt::collect(raised));
+        assert_eq!(out, vec![Ok(30), Ok(10), Err(()), Ok(20)]);
+    }
+
+    #[test]
+    fn test_filter_map_ok_pending() {
+        let items = vec![Ok("hello"), Ok(""), Err(()), Ok("world!")];
+        let expected = vec![Ok("hello".to_owned()), Err(()), Ok("world!".to_owned())];
+
+        let src = ScriptedStream::interleaved(items.clone());
+        assert_stream_yields(&mut TryFilterMapOk::new(src, filter_empty), expected.clone());
+
+        let src = ScriptedStream::interleaved(items.clone());
+        let mut raised = TryFilterMapOkFallible::new(src, |v| Ok(filter_empty(v)));
+        assert_stream_yields(&mut raised, expected.clone());
+
+        let src = ScriptedStream::interleaved(items.clone());
+        let mut raised = Box::pin(TryFilterMapOkAsync::new(src, |v| async move {
+            yield_now().await;
+            filter_empty(v)
+        }));
+        assert_stream_yields(&mut raised, expected.clone());
+
+        let src = ScriptedStream::interleaved(items);
+        let handle = src.handle();
+        let mut raised = Box::pin(TryFilterMapOkBuffered::new(src, 2, |v| async move {
+            yield_now().await;
+            filter_empty(v)
+        }));
+        assert_stream_yields(&mut raised, expected);
+        assert_eq!(handle.polls_after_end(), 0);
+    }
+
+    #[test]
+    fn test_filter_map_ok_marbles() {
+        let raised = TryFilterMapOk::new(marbles("a-x-#-b|"), |c| if c == 'x' { None } else { Some(c) });
+        assert_marbles(&mut Box::pin(raised), "a--#-b|");
+    }
+
+    async fn yield_now() {
+        let mut yielded = false;
+        futures::future::poll_fn(|cx| {
+            if yielded {
+                std::task::Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            }
+        })
+        .await
+    }
+
+    fn filter_empty_async(v: &str) -> Ready<Option<String>> {
+        ready(filter_empty(v))
+    }
+
     fn filter_empty(v: &str) -> Option<String> {
         if !v.is_empty() {
             Some(v.to_string())
@@ -0,0 +1,552 @@
+use crate::op_prelude::*;
+use crate::time::{deadline_after, skip_to, yield_now, Timer, POLL_BUDGET};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::mem;
+use std::time::{Duration, Instant};
+
+pin_project! {
+    /// Stream for the [`window_tumbling`](super::JStreamExt::window_tumbling) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct WindowTumbling<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        duration: Duration,
+        window_end: Option<Instant>,
+        #[pin]
+        delay: Option<T::Delay>,
+        items: Vec<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for WindowTumbling<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let mut taken = 0;
+        loop {
+            let now = this.timer.now();
+            let end = *this.window_end.get_or_insert(deadline_after(now, *this.duration));
+            if now >= end || *this.src_done {
+                *this.window_end = Some(skip_to(end, now, *this.duration));
+                this.delay.set(None);
+                if !this.items.is_empty() {
+                    return Poll::Ready(Some(mem::take(this.items)));
+                }
+
+                if *this.src_done {
+                    return Poll::Ready(None);
+                }
+                continue;
+            }
+
+            if taken == POLL_BUDGET {
+                return yield_now(cx);
+            }
+
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    this.items.push(next);
+                    taken += 1;
+                }
+                Poll::Ready(None) => *this.src_done = true,
+                Poll::Pending => {
+                    // empty windows are skipped, so the timer is only needed once an item arrives
+                    if this.items.is_empty() {
+                        return Poll::Pending;
+                    }
+
+                    if this.delay.is_none() {
+                        this.delay.set(Some(this.timer.delay_until(end)));
+                    }
+                    ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.src_done && self.items.is_empty() {
+            (0, Some(0))
+        } else {
+            window_size_hint(self.src.size_hint(), !self.items.is_empty())
+        }
+    }
+}
+
+impl<S, T> FusedStream for WindowTumbling<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.items.is_empty()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for WindowTumbling<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> WindowTumbling<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, duration: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            duration: duration.max(Duration::from_nanos(1)),
+            window_end: None,
+            delay: None,
+            items: Vec::new(),
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> WindowTumbling<S, T2> {
+        WindowTumbling::new(self.src, self.duration, timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`window_sliding`](super::JStreamExt::window_sliding) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct WindowSliding<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        size: Duration,
+        step: Duration,
+        window_end: Option<Instant>,
+        #[pin]
+        delay: Option<T::Delay>,
+        items: VecDeque<(Instant, S::Item)>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for WindowSliding<S, T>
+where
+    S: Stream,
+    S::Item: Clone,
+    T: Timer,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        let mut taken = 0;
+        loop {
+            let now = this.timer.now();
+            let end = *this.window_end.get_or_insert(deadline_after(now, *this.size));
+            if now >= end || *this.src_done {
+                this.delay.set(None);
+                if this.items.is_empty() {
+                    if *this.src_done {
+                        return Poll::Ready(None);
+                    }
+
+                    // nothing to emit until the next item arrives, so skip the empty windows
+                    *this.window_end = Some(skip_to(end, now, *this.step));
+                    continue;
+                }
+
+                let start = end.checked_sub(*this.size).unwrap_or(end);
+                let window: Vec<_> = this
+                    .items
+                    .iter()
+                    .filter(|(at, _)| *at >= start && *at < end)
+                    .map(|(_, item)| item.clone())
+                    .collect();
+
+                // drop anything which falls before the start of the next window
+                let next_end = deadline_after(end, *this.step);
+                let next_start = next_end.checked_sub(*this.size).unwrap_or(next_end);
+                while this.items.front().is_some_and(|(at, _)| *at < next_start) {
+                    this.items.pop_front();
+                }
+                *this.window_end = Some(next_end);
+
+                if !window.is_empty() {
+                    return Poll::Ready(Some(window));
+                }
+                continue;
+            }
+
+            if taken == POLL_BUDGET {
+                return yield_now(cx);
+            }
+
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    this.items.push_back((now, next));
+                    taken += 1;
+                }
+                Poll::Ready(None) => *this.src_done = true,
+                Poll::Pending => {
+                    if this.items.is_empty() {
+                        return Poll::Pending;
+                    }
+
+                    if this.delay.is_none() {
+                        this.delay.set(Some(this.timer.delay_until(end)));
+                    }
+                    ready!(this.delay.as_mut().as_pin_mut().expect("set above").poll(cx));
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.src_done && self.items.is_empty() {
+            (0, Some(0))
+        } else {
+            // every item can show up in this many windows
+            let windows = self.size.as_nanos().div_ceil(self.step.as_nanos());
+            let windows = usize::try_from(windows.max(1)).unwrap_or(usize::MAX);
+            let (lower, upper) = self.src.size_hint();
+            let buffered = self.items.len();
+            (
+                if lower > 0 || buffered > 0 { 1 } else { 0 },
+                upper
+                    .and_then(|upper| upper.checked_add(buffered))
+                    .and_then(|upper| upper.checked_mul(windows)),
+            )
+        }
+    }
+}
+
+impl<S, T> FusedStream for WindowSliding<S, T>
+where
+    S: Stream,
+    S::Item: Clone,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done && self.items.is_empty()
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for WindowSliding<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> WindowSliding<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, size: Duration, step: Duration, timer: T) -> Self {
+        assert!(!size.is_zero(), "window_sliding size must be greater than zero");
+        Self {
+            src,
+            timer,
+            size,
+            step: step.max(Duration::from_nanos(1)),
+            window_end: None,
+            delay: None,
+            items: VecDeque::new(),
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> WindowSliding<S, T2> {
+        WindowSliding::new(self.src, self.size, self.step, timer)
+    }
+}
+
+pin_project! {
+    /// Stream for the [`window_session`](super::JStreamExt::window_session) method
+    #[must_use = "streams do nothing unless polled"]
+    pub struct WindowSession<S, T>
+    where
+        S: Stream,
+        T: Timer,
+    {
+        #[pin]
+        src: S,
+        timer: T,
+        gap: Duration,
+        #[pin]
+        delay: Option<T::Delay>,
+        items: Vec<S::Item>,
+        src_done: bool,
+    }
+}
+
+impl<S, T> Stream for WindowSession<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        if *this.src_done {
+            return Poll::Ready(None);
+        }
+
+        // every item extends the session, so take everything the upstream has (up to the budget)
+        // and restart the gap if anything was received
+        let mut received = false;
+        let mut src_pending = false;
+        for _ in 0..POLL_BUDGET {
+            match this.src.as_mut().poll_next(cx) {
+                Poll::Ready(Some(next)) => {
+                    this.items.push(next);
+                    received = true;
+                }
+                Poll::Ready(None) => {
+                    *this.src_done = true;
+                    this.delay.set(None);
+                    return Poll::Ready(if this.items.is_empty() {
+                        None
+                    } else {
+                        Some(mem::take(this.items))
+                    });
+                }
+                Poll::Pending => {
+                    src_pending = true;
+                    break;
+                }
+            }
+        }
+
+        if received {
+            this.delay.set(Some(this.timer.delay(*this.gap)));
+        }
+
+        if !src_pending {
+            return yield_now(cx);
+        }
+
+        if let Some(delay) = this.delay.as_mut().as_pin_mut() {
+            ready!(delay.poll(cx));
+            this.delay.set(None);
+            return Poll::Ready(Some(mem::take(this.items)));
+        }
+
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.src_done {
+            (0, Some(0))
+        } else {
+            window_size_hint(self.src.size_hint(), !self.items.is_empty())
+        }
+    }
+}
+
+impl<S, T> FusedStream for WindowSession<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.src_done
+    }
+}
+
+#[cfg(feature = "sink")]
+impl<S, T, Item> Sink<Item> for WindowSession<S, T>
+where
+    S: Stream + Sink<Item>,
+    T: Timer,
+{
+    delegate_sink!(src, S::Error, Item);
+}
+
+impl<S, T> WindowSession<S, T>
+where
+    S: Stream,
+    T: Timer,
+{
+    pub(crate) fn new(src: S, gap: Duration, timer: T) -> Self {
+        Self {
+            src,
+            timer,
+            gap,
+            delay: None,
+            items: Vec::new(),
+            src_done: false,
+        }
+    }
+
+    /// Replace the [`Timer`](crate::time::Timer) used by this stream, see [Time](crate#time)
+    pub fn with_timer<T2: Timer>(self, timer: T2) -> WindowSession<S, T2> {
+        WindowSession::new(self.src, self.gap, timer)
+    }
+}
+
+fn window_size_hint(
+    (src_lower, src_upper): (usize, Option<usize>),
+    has_items: bool,
+) -> (usize, Option<usize>) {
+    let buffered = if has_items { 1 } else { 0 };
+    (
+        if src_lower > 0 || has_items { 1 } else { 0 },
+        src_upper.and_then(|upper| upper.checked_add(buffered)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WindowSession, WindowSliding, WindowTumbling};
+    use crate::test_util::TimedHarness;
+    use futures::stream;
+    use std::task::Poll;
+    use std::time::Duration;
+
+    #[test]
+    fn test_window_tumbling() {
+        let mut harness = TimedHarness::new();
+        let mut windows = WindowTumbling::new(
+            harness.source(),
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        // windows are aligned to the first poll
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(500));
+        harness.send(1);
+        harness.send(2);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        let wakes = harness.wakes();
+        harness.advance(Duration::from_millis(500));
+        assert_eq!(harness.wakes(), wakes + 1);
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![1, 2])));
+
+        // empty windows are skipped
+        harness.advance(Duration::from_millis(3200));
+        harness.send(3);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(799));
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![3])));
+
+        // the open window is flushed when the source ends
+        harness.send(4);
+        harness.close();
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![4])));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_window_sliding() {
+        let mut harness = TimedHarness::new();
+        let mut windows = WindowSliding::new(
+            harness.source(),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.send(1);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        harness.send(2);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+
+        // [0s, 2s) has both items, [1s, 3s) only has the second one
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![1, 2])));
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_secs(1));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![2])));
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+
+        // every window which holds an item is flushed when the source ends
+        harness.send(3);
+        harness.close();
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![3])));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![3])));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(None));
+    }
+
+    #[test]
+    fn test_window_session() {
+        let mut harness = TimedHarness::new();
+        let mut windows = WindowSession::new(
+            harness.source(),
+            Duration::from_secs(1),
+            harness.clock(),
+        );
+
+        harness.send(1);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(900));
+        harness.send(2);
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(900));
+        assert_eq!(harness.poll(&mut windows), Poll::Pending);
+        harness.advance(Duration::from_millis(100));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![1, 2])));
+
+        harness.send(3);
+        harness.close();
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(Some(vec![3])));
+        assert_eq!(harness.poll(&mut windows), Poll::Ready(None));
+    }
+
+    #[test]
+    #[should_panic(expected = "size must be greater than zero")]
+    fn test_window_sliding_zero_size() {
+        let harness: TimedHarness = TimedHarness::new();
+        let second = Duration::from_secs(1);
+        let _ = WindowSliding::new(stream::repeat(1), Duration::ZERO, second, harness.clock());
+    }
+
+    #[test]
+    fn test_windows_always_ready_source() {
+        // a source which never goes quiet still lets every window return from each poll
+        let harness: TimedHarness = TimedHarness::new();
+        let second = Duration::from_secs(1);
+        let mut tumbling = WindowTumbling::new(stream::repeat(1), second, harness.clock());
+        assert_eq!(harness.poll(&mut tumbling), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+
+        let harness: TimedHarness = TimedHarness::new();
+        let mut sliding = WindowSliding::new(stream::repeat(1), second, second, harness.clock());
+        assert_eq!(harness.poll(&mut sliding), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+
+        let harness: TimedHarness = TimedHarness::new();
+        let mut session = WindowSession::new(stream::repeat(1), second, harness.clock());
+        assert_eq!(harness.poll(&mut session), Poll::Pending);
+        assert_eq!(harness.wakes(), 1);
+    }
+}